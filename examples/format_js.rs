@@ -1,5 +1,5 @@
 use anyhow::Result;
-use neatify::{format, format_dir};
+use neatify::{format, format_dir, FormatOptions};
 
 fn main() -> Result<()> {
     // Example JavaScript code to format
@@ -14,8 +14,9 @@ fn main() -> Result<()> {
 
     println!("Original file created at: {}", file_path.display());
 
-    // Format the file
-    let formatted = format(&file_path, true)?;
+    // Format the file with the default style
+    let options = FormatOptions::default();
+    let formatted = format(&file_path, true, &options)?;
     println!("File formatted: {}", formatted);
 
     // Read and display the formatted content
@@ -24,7 +25,7 @@ fn main() -> Result<()> {
 
     // Format a directory (if it contains JavaScript files)
     println!("\nFormatting directory: {}", temp_dir.display());
-    let stats = format_dir(&temp_dir, true)?;
+    let stats = format_dir(&temp_dir, true, &options)?;
 
     // Display formatting statistics
     println!("\nFormatting statistics:");
@@ -34,6 +35,10 @@ fn main() -> Result<()> {
         "  Files needing formatting: {}",
         stats.files_needing_formatting
     );
+    println!("  Files that failed to parse: {}", stats.failed_files);
+    for error in &stats.errors {
+        println!("    {}", error);
+    }
 
     // Clean up
     std::fs::remove_file(file_path)?;
@@ -0,0 +1,275 @@
+//! Project configuration file discovery
+//!
+//! Configuration is read from `neatify.toml` or `.neatifyrc.json` files. For each
+//! formatted file, every config file from the file's directory up to the filesystem
+//! root (or up to a config with `root = true`) is applied, with the nearest file winning.
+//! The `overrides` section of a config maps glob patterns, relative to the config file,
+//! to option sets that are applied on top of that file's options for matching files,
+//! before any nearer config file is applied.
+
+use crate::core::error::{Location, NeatifyError, Result};
+use crate::core::options::{BraceStyle, EndOfLine, FormatOptions, QuoteStyle, TrailingCommas};
+use globset::{Glob, GlobSet, GlobSetBuilder};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+
+/// Config file names, in order of precedence within a single directory
+pub const CONFIG_FILE_NAMES: [&str; 2] = ["neatify.toml", ".neatifyrc.json"];
+
+/// Contents of a single configuration file
+#[derive(Debug, Default, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Config {
+    /// Stop looking for configuration in parent directories
+    #[serde(default)]
+    pub root: bool,
+    pub indent_width: Option<usize>,
+    pub use_tabs: Option<bool>,
+    pub quote_style: Option<QuoteStyle>,
+    pub print_width: Option<usize>,
+    pub semicolons: Option<bool>,
+    pub trailing_commas: Option<TrailingCommas>,
+    pub end_of_line: Option<EndOfLine>,
+    pub normalize_numbers: Option<bool>,
+    pub brace_style: Option<BraceStyle>,
+    /// Option sets applied to files matching glob patterns
+    #[serde(default)]
+    pub overrides: Vec<Override>,
+}
+
+/// Options applied to the files matching a set of glob patterns
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Override {
+    /// Glob patterns of the files this override applies to
+    pub files: Vec<String>,
+    /// Glob patterns of files excluded from this override
+    #[serde(default)]
+    pub excluded_files: Vec<String>,
+    /// Options for the matching files
+    pub options: Config,
+    #[serde(skip)]
+    matcher: Option<OverrideMatcher>,
+}
+
+/// Compiled glob patterns of an override
+#[derive(Debug, Clone)]
+struct OverrideMatcher {
+    /// Directory of the config file the patterns are relative to
+    base_dir: PathBuf,
+    files: GlobSet,
+    excluded_files: GlobSet,
+}
+
+impl Override {
+    /// Check if this override applies to the given absolute file path
+    pub fn matches(&self, file_path: &Path) -> bool {
+        let Some(matcher) = &self.matcher else {
+            return false;
+        };
+        let Ok(relative) = file_path.strip_prefix(&matcher.base_dir) else {
+            return false;
+        };
+
+        glob_set_matches(&matcher.files, relative)
+            && !glob_set_matches(&matcher.excluded_files, relative)
+    }
+
+    /// Compile the glob patterns relative to `base_dir`
+    fn compile(&mut self, config_path: &Path, base_dir: &Path) -> Result<()> {
+        if self.options.root || !self.options.overrides.is_empty() {
+            return Err(config_error(
+                config_path,
+                None,
+                "`root` and `overrides` are not allowed inside an override",
+            ));
+        }
+
+        self.matcher = Some(OverrideMatcher {
+            base_dir: base_dir.to_path_buf(),
+            files: build_glob_set(config_path, &self.files)?,
+            excluded_files: build_glob_set(config_path, &self.excluded_files)?,
+        });
+        Ok(())
+    }
+}
+
+/// Build a glob set, reporting invalid patterns as config errors
+fn build_glob_set(config_path: &Path, patterns: &[String]) -> Result<GlobSet> {
+    let mut builder = GlobSetBuilder::new();
+    for pattern in patterns {
+        // Patterns without a separator match the file name in any directory
+        let pattern = if pattern.contains('/') {
+            pattern.trim_start_matches("./").to_string()
+        } else {
+            format!("**/{}", pattern)
+        };
+        let glob =
+            Glob::new(&pattern).map_err(|e| config_error(config_path, None, &e.to_string()))?;
+        builder.add(glob);
+    }
+    builder
+        .build()
+        .map_err(|e| config_error(config_path, None, &e.to_string()))
+}
+
+/// Match a relative path against a glob set using `/` separators
+fn glob_set_matches(set: &GlobSet, relative: &Path) -> bool {
+    let relative = relative
+        .components()
+        .map(|component| component.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/");
+    set.is_match(relative)
+}
+
+impl Config {
+    /// Load a configuration file, choosing the format from its file name
+    pub fn load(path: &Path) -> Result<Config> {
+        let content = fs::read_to_string(path).map_err(|e| NeatifyError::io(path, e))?;
+
+        let mut config: Config = if path.extension().is_some_and(|ext| ext == "json") {
+            serde_json::from_str(&content).map_err(|e| {
+                let location = Location::from_line_column(&content, e.line(), e.column());
+                // The position is reported through the location, not the message
+                let message = e.to_string();
+                let suffix = format!(" at line {} column {}", e.line(), e.column());
+                let message = message.strip_suffix(&suffix).unwrap_or(&message);
+                config_error(path, Some(location), message)
+            })?
+        } else {
+            toml::from_str(&content).map_err(|e| {
+                let location = e
+                    .span()
+                    .map(|span| Location::from_offset(&content, span.start));
+                config_error(path, location, e.message())
+            })?
+        };
+
+        let path = absolute_path(path)?;
+        let base_dir = path.parent().unwrap_or(Path::new(""));
+        for override_config in &mut config.overrides {
+            override_config.compile(&path, base_dir)?;
+        }
+
+        Ok(config)
+    }
+
+    /// Apply this configuration and the overrides matching `file_path` to `options`
+    pub fn apply_for_file(&self, file_path: &Path, options: &mut FormatOptions) {
+        self.apply(options);
+        for override_config in &self.overrides {
+            if override_config.matches(file_path) {
+                override_config.options.apply(options);
+            }
+        }
+    }
+
+    /// Apply the values set in this configuration to `options`
+    pub fn apply(&self, options: &mut FormatOptions) {
+        apply_field(&mut options.indent_width, self.indent_width);
+        apply_field(&mut options.use_tabs, self.use_tabs);
+        apply_field(&mut options.quote_style, self.quote_style);
+        apply_field(&mut options.print_width, self.print_width);
+        apply_field(&mut options.semicolons, self.semicolons);
+        apply_field(&mut options.trailing_commas, self.trailing_commas);
+        apply_field(&mut options.end_of_line, self.end_of_line);
+        apply_field(&mut options.normalize_numbers, self.normalize_numbers);
+        apply_field(&mut options.brace_style, self.brace_style);
+    }
+}
+
+fn apply_field<T: Copy>(target: &mut T, value: Option<T>) {
+    if let Some(value) = value {
+        *target = value;
+    }
+}
+
+/// Build a config error pointing at a location in a config file
+fn config_error(path: &Path, location: Option<Location>, message: &str) -> NeatifyError {
+    NeatifyError::Config {
+        path: path.to_path_buf(),
+        location,
+        message: message.to_string(),
+    }
+}
+
+/// Resolves and caches the configuration files that apply to directories
+#[derive(Debug, Default)]
+pub struct ConfigResolver {
+    cache: HashMap<PathBuf, Vec<Config>>,
+}
+
+impl ConfigResolver {
+    /// Create a new resolver with an empty cache
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Resolve the formatting options for a file, starting from `base`
+    pub fn resolve(&mut self, file_path: &Path, base: &FormatOptions) -> Result<FormatOptions> {
+        let file_path = absolute_path(file_path)?;
+        let mut options = base.clone();
+
+        if let Some(dir) = file_path.parent() {
+            for config in self.configs_for_dir(dir)? {
+                config.apply_for_file(&file_path, &mut options);
+            }
+        }
+
+        Ok(options)
+    }
+
+    /// Get the configuration files that apply to files in `dir`, farthest first
+    fn configs_for_dir(&mut self, dir: &Path) -> Result<Vec<Config>> {
+        if let Some(configs) = self.cache.get(dir) {
+            return Ok(configs.clone());
+        }
+
+        let own = find_config_file(dir)
+            .map(|path| Config::load(&path))
+            .transpose()?;
+
+        let configs = match own {
+            Some(own) if own.root => vec![own],
+            own => {
+                let mut configs = match dir.parent() {
+                    Some(parent) => self.configs_for_dir(parent)?,
+                    None => Vec::new(),
+                };
+                configs.extend(own);
+                configs
+            }
+        };
+
+        self.cache.insert(dir.to_path_buf(), configs.clone());
+        Ok(configs)
+    }
+}
+
+/// Make `path` absolute and resolve its `.` and `..` components lexically, so that
+/// walking up its parents visits the directories it is really in
+fn absolute_path(path: &Path) -> Result<PathBuf> {
+    let absolute = std::path::absolute(path).map_err(|e| NeatifyError::io(path, e))?;
+    let mut normalized = PathBuf::new();
+    for component in absolute.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                normalized.pop();
+            }
+            component => normalized.push(component),
+        }
+    }
+    Ok(normalized)
+}
+
+/// Find the configuration file in a directory, if any
+fn find_config_file(dir: &Path) -> Option<PathBuf> {
+    CONFIG_FILE_NAMES
+        .iter()
+        .map(|name| dir.join(name))
+        .find(|path| path.is_file())
+}
@@ -0,0 +1,429 @@
+//! Document IR for line-width-aware pretty printing
+//!
+//! Formatters describe their output as a `Doc` built from text, possible line breaks,
+//! groups and indentation. The printer then decides which line breaks to take so
+//! that the output stays within `FormatOptions::print_width`, in the style of
+//! Wadler's "prettier printer" and Prettier's document printer.
+
+use crate::core::options::FormatOptions;
+
+/// A document describing formatted output
+#[derive(Debug, Clone, PartialEq)]
+pub enum Doc {
+    /// Literal text
+    Text(String),
+    /// Documents printed one after another
+    Concat(Vec<Doc>),
+    /// A document whose line breaks are indented one more level
+    Indent(Box<Doc>),
+    /// A document printed on one line if it fits, otherwise with all of its own
+    /// lines broken. A group containing a hard line break always breaks.
+    Group {
+        contents: Box<Doc>,
+        should_break: bool,
+    },
+    /// Alternating contents and separators, breaking only the separators needed to
+    /// keep each line within the print width
+    Fill(Vec<Doc>),
+    /// Different documents depending on whether the enclosing group is broken
+    IfBreak { broken: Box<Doc>, flat: Box<Doc> },
+    /// A line break, or a space when the enclosing group is flat
+    Line,
+    /// A line break, or nothing when the enclosing group is flat
+    SoftLine,
+    /// A line break that is always taken
+    HardLine,
+    /// Force the enclosing groups to break
+    BreakParent,
+    /// Contents printed at the end of the line, such as a trailing comment. Line
+    /// suffixes do not count towards the width of the line.
+    LineSuffix(Box<Doc>),
+}
+
+impl Doc {
+    /// Literal text
+    pub fn text(text: impl Into<String>) -> Doc {
+        Doc::Text(text.into())
+    }
+
+    /// Documents printed one after another
+    pub fn concat(docs: Vec<Doc>) -> Doc {
+        Doc::Concat(docs)
+    }
+
+    /// Indent the line breaks in `doc` one more level
+    pub fn indent(doc: Doc) -> Doc {
+        Doc::Indent(Box::new(doc))
+    }
+
+    /// Print `doc` on one line if it fits, otherwise break its lines
+    pub fn group(doc: Doc) -> Doc {
+        let should_break = doc.forces_break();
+        Doc::Group {
+            contents: Box::new(doc),
+            should_break,
+        }
+    }
+
+    /// Alternating contents and separators, such as words and `Doc::line()`
+    pub fn fill(parts: Vec<Doc>) -> Doc {
+        Doc::Fill(parts)
+    }
+
+    /// `broken` if the enclosing group breaks, `flat` otherwise
+    pub fn if_break(broken: Doc, flat: Doc) -> Doc {
+        Doc::IfBreak {
+            broken: Box::new(broken),
+            flat: Box::new(flat),
+        }
+    }
+
+    /// A line break, or a space when flat
+    pub fn line() -> Doc {
+        Doc::Line
+    }
+
+    /// A line break, or nothing when flat
+    pub fn softline() -> Doc {
+        Doc::SoftLine
+    }
+
+    /// A line break that is always taken
+    pub fn hardline() -> Doc {
+        Doc::HardLine
+    }
+
+    /// Force the enclosing groups to break
+    pub fn break_parent() -> Doc {
+        Doc::BreakParent
+    }
+
+    /// Print `doc` just before the next line break
+    pub fn line_suffix(doc: Doc) -> Doc {
+        Doc::LineSuffix(Box::new(doc))
+    }
+
+    /// The empty document
+    pub fn nil() -> Doc {
+        Doc::Concat(Vec::new())
+    }
+
+    /// Join `docs` with copies of `separator`
+    pub fn join(separator: Doc, docs: impl IntoIterator<Item = Doc>) -> Doc {
+        let mut parts = Vec::new();
+        for (index, doc) in docs.into_iter().enumerate() {
+            if index > 0 {
+                parts.push(separator.clone());
+            }
+            parts.push(doc);
+        }
+        Doc::Concat(parts)
+    }
+
+    /// Check if the document contains a hard line break, which breaks every group
+    /// around it
+    pub fn forces_break(&self) -> bool {
+        match self {
+            Doc::HardLine | Doc::BreakParent => true,
+            Doc::Text(_) | Doc::Line | Doc::SoftLine => false,
+            Doc::Concat(docs) | Doc::Fill(docs) => docs.iter().any(Doc::forces_break),
+            Doc::Indent(doc) | Doc::LineSuffix(doc) => doc.forces_break(),
+            Doc::Group { should_break, .. } => *should_break,
+            Doc::IfBreak { broken, flat } => broken.forces_break() || flat.forces_break(),
+        }
+    }
+
+    /// Print the document within the print width and indentation of `options`
+    ///
+    /// Lines are separated by `\n` and trailing whitespace is removed.
+    pub fn print(&self, options: &FormatOptions) -> String {
+        Printer::new(options).print(self)
+    }
+}
+
+impl From<&str> for Doc {
+    fn from(text: &str) -> Doc {
+        Doc::text(text)
+    }
+}
+
+impl From<String> for Doc {
+    fn from(text: String) -> Doc {
+        Doc::Text(text)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Mode {
+    Break,
+    Flat,
+}
+
+/// A document, or the remaining parts of a `Fill`, waiting to be printed
+#[derive(Clone, Copy)]
+enum Content<'a> {
+    Doc(&'a Doc),
+    Fill(&'a [Doc]),
+}
+
+#[derive(Clone, Copy)]
+struct Command<'a> {
+    indent: usize,
+    mode: Mode,
+    content: Content<'a>,
+}
+
+impl<'a> Command<'a> {
+    fn new(indent: usize, mode: Mode, doc: &'a Doc) -> Self {
+        Self {
+            indent,
+            mode,
+            content: Content::Doc(doc),
+        }
+    }
+}
+
+/// Number of columns `text` takes up on the line it ends on
+fn text_width(text: &str) -> usize {
+    text.rsplit('\n').next().unwrap_or("").chars().count()
+}
+
+struct Printer {
+    width: usize,
+    indent_unit: String,
+    /// Columns taken by one level of indentation
+    indent_width: usize,
+}
+
+impl Printer {
+    fn new(options: &FormatOptions) -> Self {
+        Self {
+            width: options.print_width,
+            indent_unit: options.indent_unit(),
+            indent_width: options.indent_width,
+        }
+    }
+
+    fn print(&self, doc: &Doc) -> String {
+        let mut out = String::new();
+        let mut column = 0;
+        let mut commands = vec![Command::new(0, Mode::Break, doc)];
+        let mut line_suffixes: Vec<Command> = Vec::new();
+
+        while let Some(command) = commands.pop() {
+            let Command {
+                indent,
+                mode,
+                content,
+            } = command;
+
+            let doc = match content {
+                Content::Doc(doc) => doc,
+                Content::Fill(parts) => {
+                    self.print_fill(indent, mode, parts, column, &mut commands);
+                    continue;
+                }
+            };
+
+            match doc {
+                Doc::Text(text) => {
+                    out.push_str(text);
+                    if text.contains('\n') {
+                        column = text_width(text);
+                    } else {
+                        column += text.chars().count();
+                    }
+                }
+                Doc::Concat(docs) => {
+                    commands.extend(docs.iter().rev().map(|doc| Command::new(indent, mode, doc)));
+                }
+                Doc::Indent(doc) => commands.push(Command::new(indent + 1, mode, doc)),
+                Doc::Group {
+                    contents,
+                    should_break,
+                } => {
+                    let flat = Command::new(indent, Mode::Flat, contents);
+                    let fits = !should_break
+                        && (mode == Mode::Flat
+                            || self.fits(vec![flat], &commands, self.remaining(column), false));
+                    if fits {
+                        commands.push(flat);
+                    } else {
+                        commands.push(Command::new(indent, Mode::Break, contents));
+                    }
+                }
+                Doc::Fill(parts) => commands.push(Command {
+                    indent,
+                    mode,
+                    content: Content::Fill(parts),
+                }),
+                Doc::IfBreak { broken, flat } => {
+                    let doc = if mode == Mode::Break { broken } else { flat };
+                    commands.push(Command::new(indent, mode, doc));
+                }
+                Doc::Line | Doc::SoftLine if mode == Mode::Flat => {
+                    if *doc == Doc::Line {
+                        out.push(' ');
+                        column += 1;
+                    }
+                }
+                Doc::Line | Doc::SoftLine | Doc::HardLine if !line_suffixes.is_empty() => {
+                    // Print the line suffixes first, then come back to the line break
+                    commands.push(command);
+                    commands.extend(line_suffixes.drain(..).rev());
+                }
+                Doc::Line | Doc::SoftLine | Doc::HardLine => {
+                    // Remove trailing whitespace before the line break
+                    let trimmed = out.trim_end_matches([' ', '\t']).len();
+                    out.truncate(trimmed);
+                    out.push('\n');
+                    for _ in 0..indent {
+                        out.push_str(&self.indent_unit);
+                    }
+                    column = indent * self.indent_width;
+                }
+                Doc::LineSuffix(doc) => line_suffixes.push(Command::new(indent, mode, doc)),
+                Doc::BreakParent => {}
+            }
+
+            if commands.is_empty() && !line_suffixes.is_empty() {
+                commands.extend(line_suffixes.drain(..).rev());
+            }
+        }
+
+        let trimmed = out.trim_end_matches([' ', '\t']).len();
+        out.truncate(trimmed);
+        out
+    }
+
+    /// Columns left on the current line
+    fn remaining(&self, column: usize) -> isize {
+        self.width as isize - column as isize
+    }
+
+    /// Queue the parts of a `Fill`, breaking a separator only if the content after
+    /// it does not fit on the current line
+    fn print_fill<'a>(
+        &self,
+        indent: usize,
+        mode: Mode,
+        parts: &'a [Doc],
+        column: usize,
+        commands: &mut Vec<Command<'a>>,
+    ) {
+        let Some(content) = parts.first() else {
+            return;
+        };
+        let remaining = self.remaining(column);
+
+        let content_flat = Command::new(indent, Mode::Flat, content);
+        let content_fits = self.fits(vec![content_flat], &[], remaining, true);
+        let content_mode = if content_fits {
+            Mode::Flat
+        } else {
+            Mode::Break
+        };
+
+        let Some(separator) = parts.get(1) else {
+            commands.push(Command::new(indent, content_mode, content));
+            return;
+        };
+
+        if let Some(next) = parts.get(2) {
+            // Break the separator if the next content does not fit after it
+            let both = vec![
+                Command::new(indent, Mode::Flat, next),
+                Command::new(indent, Mode::Flat, separator),
+                content_flat,
+            ];
+            let separator_mode = if self.fits(both, &[], remaining, true) {
+                Mode::Flat
+            } else {
+                Mode::Break
+            };
+            commands.push(Command {
+                indent,
+                mode,
+                content: Content::Fill(&parts[2..]),
+            });
+            commands.push(Command::new(indent, separator_mode, separator));
+        } else {
+            commands.push(Command::new(indent, content_mode, separator));
+        }
+        commands.push(Command::new(indent, content_mode, content));
+    }
+
+    /// Check if `next` fits in `width` columns, followed by the rest of the line from
+    /// the queued `rest` commands
+    fn fits<'a>(
+        &self,
+        mut next: Vec<Command<'a>>,
+        rest: &[Command<'a>],
+        mut width: isize,
+        must_be_flat: bool,
+    ) -> bool {
+        let mut rest_index = rest.len();
+
+        while width >= 0 {
+            let Some(command) = next.pop() else {
+                // The rest of the line comes from the queued commands
+                if rest_index == 0 {
+                    return true;
+                }
+                rest_index -= 1;
+                next.push(rest[rest_index]);
+                continue;
+            };
+            let Command {
+                indent,
+                mode,
+                content,
+            } = command;
+
+            let doc = match content {
+                Content::Doc(doc) => doc,
+                Content::Fill(parts) => {
+                    next.extend(
+                        parts
+                            .iter()
+                            .rev()
+                            .map(|doc| Command::new(indent, mode, doc)),
+                    );
+                    continue;
+                }
+            };
+
+            match doc {
+                // Text spanning several lines only has to fit up to its first line break
+                Doc::Text(text) => match text.split_once('\n') {
+                    Some((first, _)) => return width >= first.chars().count() as isize,
+                    None => width -= text.chars().count() as isize,
+                },
+                Doc::Concat(docs) | Doc::Fill(docs) => {
+                    next.extend(docs.iter().rev().map(|doc| Command::new(indent, mode, doc)));
+                }
+                Doc::Indent(doc) => next.push(Command::new(indent + 1, mode, doc)),
+                Doc::Group {
+                    contents,
+                    should_break,
+                } => {
+                    if must_be_flat && *should_break {
+                        return false;
+                    }
+                    let mode = if *should_break { Mode::Break } else { mode };
+                    next.push(Command::new(indent, mode, contents));
+                }
+                Doc::IfBreak { broken, flat } => {
+                    let doc = if mode == Mode::Break { broken } else { flat };
+                    next.push(Command::new(indent, mode, doc));
+                }
+                Doc::Line if mode == Mode::Flat => width -= 1,
+                Doc::SoftLine if mode == Mode::Flat => {}
+                Doc::Line | Doc::SoftLine | Doc::HardLine => return true,
+                Doc::LineSuffix(_) | Doc::BreakParent => {}
+            }
+        }
+
+        false
+    }
+}
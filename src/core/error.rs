@@ -1,31 +1,369 @@
 //! Error handling for the neatify library
 
+use crate::core::tokens::{is_line_terminator, line_break_count};
 use std::fmt;
+use std::path::{Path, PathBuf};
 
+/// Result type used throughout the neatify library
+pub type Result<T, E = NeatifyError> = std::result::Result<T, E>;
+
+/// A position in a source or configuration file
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Location {
+    /// Byte offset from the start of the file
+    pub offset: usize,
+    /// 1-based line number
+    pub line: usize,
+    /// 1-based column, counted in characters
+    pub column: usize,
+}
+
+impl Location {
+    /// Compute the location of a byte offset in `content`
+    pub fn from_offset(content: &str, offset: usize) -> Self {
+        let mut offset = offset.min(content.len());
+        while !content.is_char_boundary(offset) {
+            offset -= 1;
+        }
+
+        let before = &content[..offset];
+        let line = before.rsplit(is_line_terminator).next().unwrap_or_default();
+        Self {
+            offset,
+            line: line_break_count(before) + 1,
+            column: line.chars().count() + 1,
+        }
+    }
+
+    /// Compute the location of a 1-based line and column in `content`
+    pub fn from_line_column(content: &str, line: usize, column: usize) -> Self {
+        let line_start = content
+            .split_inclusive('\n')
+            .take(line.saturating_sub(1))
+            .map(str::len)
+            .sum::<usize>();
+        let column_offset = content[line_start..]
+            .chars()
+            .take(column.saturating_sub(1))
+            .map(char::len_utf8)
+            .sum::<usize>();
+        Self {
+            offset: line_start + column_offset,
+            line,
+            column,
+        }
+    }
+}
+
+impl fmt::Display for Location {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.line, self.column)
+    }
+}
+
+/// Kinds of problems found while tokenizing source code
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LexErrorKind {
+    /// A string literal is not closed before the end of the line or file
+    UnterminatedString,
+    /// A block comment is not closed before the end of the file
+    UnterminatedBlockComment,
+    /// A template literal is not closed before the end of the file
+    UnterminatedTemplate,
+    /// A regular expression literal is not closed before the end of the line
+    UnterminatedRegex,
+    /// A `\\u` escape in an identifier that is malformed or does not encode an
+    /// identifier character
+    InvalidEscape,
+    /// A numeric literal with a missing or invalid digit, such as `0x` or `1e+`
+    InvalidNumber,
+    /// A character that is not allowed outside of strings and comments
+    InvalidCharacter(char),
+}
+
+impl fmt::Display for LexErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LexErrorKind::UnterminatedString => write!(f, "Unterminated string literal"),
+            LexErrorKind::UnterminatedBlockComment => write!(f, "Unterminated block comment"),
+            LexErrorKind::UnterminatedTemplate => write!(f, "Unterminated template literal"),
+            LexErrorKind::UnterminatedRegex => {
+                write!(f, "Unterminated regular expression literal")
+            }
+            LexErrorKind::InvalidEscape => write!(f, "Invalid Unicode escape sequence"),
+            LexErrorKind::InvalidNumber => write!(f, "Invalid numeric literal"),
+            LexErrorKind::InvalidCharacter(c) => {
+                write!(f, "Invalid character {:?} (U+{:04X})", c, *c as u32)
+            }
+        }
+    }
+}
+
+/// Error returned by a tokenizer for input it cannot tokenize
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LexError {
+    /// What went wrong
+    pub kind: LexErrorKind,
+    /// Where the offending token starts
+    pub location: Location,
+}
+
+impl fmt::Display for LexError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.location, self.kind)
+    }
+}
+
+impl std::error::Error for LexError {}
+
+/// Errors returned by neatify
 #[derive(Debug)]
 pub enum NeatifyError {
+    /// Error when source code cannot be tokenized
+    Lex {
+        /// File containing the source code, if known
+        path: Option<PathBuf>,
+        /// What went wrong and where
+        error: LexError,
+    },
+    /// Error when source code is not syntactically valid
+    Syntax {
+        /// File containing the source code, if known
+        path: Option<PathBuf>,
+        /// Position of the offending token
+        location: Location,
+        /// Description of the problem
+        message: String,
+    },
     /// Error when a file is not supported by any formatter
-    UnsupportedFile(String),
+    UnsupportedFile {
+        /// The unsupported file
+        path: PathBuf,
+    },
     /// Error when a file cannot be read or written
-    IoError(std::io::Error),
+    Io {
+        /// The file or directory being accessed, if known
+        path: Option<PathBuf>,
+        /// The underlying I/O error
+        source: std::io::Error,
+    },
+    /// Error when a configuration file is invalid
+    Config {
+        /// The configuration file
+        path: PathBuf,
+        /// Position of the problem in the configuration file, if known
+        location: Option<Location>,
+        /// Description of the problem
+        message: String,
+    },
     /// Error when formatting fails
-    FormattingError(String),
+    Formatting {
+        /// File being formatted, if known
+        path: Option<PathBuf>,
+        /// Position of the problem in the source code, if known
+        location: Option<Location>,
+        /// Description of the problem
+        message: String,
+    },
+}
+
+impl NeatifyError {
+    /// Create an I/O error for a file
+    pub fn io<P: Into<PathBuf>>(path: P, source: std::io::Error) -> Self {
+        NeatifyError::Io {
+            path: Some(path.into()),
+            source,
+        }
+    }
+
+    /// Create an error for a file that no formatter supports
+    pub fn unsupported_file<P: Into<PathBuf>>(path: P) -> Self {
+        NeatifyError::UnsupportedFile { path: path.into() }
+    }
+
+    /// The file the error occurred in, if known
+    pub fn path(&self) -> Option<&Path> {
+        match self {
+            NeatifyError::Lex { path, .. }
+            | NeatifyError::Syntax { path, .. }
+            | NeatifyError::Io { path, .. }
+            | NeatifyError::Formatting { path, .. } => path.as_deref(),
+            NeatifyError::UnsupportedFile { path } | NeatifyError::Config { path, .. } => {
+                Some(path)
+            }
+        }
+    }
+
+    /// The position in the file the error occurred at, if known
+    pub fn location(&self) -> Option<Location> {
+        match self {
+            NeatifyError::Lex { error, .. } => Some(error.location),
+            NeatifyError::Syntax { location, .. } => Some(*location),
+            NeatifyError::Config { location, .. } | NeatifyError::Formatting { location, .. } => {
+                *location
+            }
+            NeatifyError::UnsupportedFile { .. } | NeatifyError::Io { .. } => None,
+        }
+    }
+
+    /// Check if the error means the source code could not be parsed
+    pub fn is_parse_error(&self) -> bool {
+        matches!(
+            self,
+            NeatifyError::Lex { .. }
+                | NeatifyError::Syntax { .. }
+                | NeatifyError::Formatting { .. }
+        )
+    }
+
+    /// Attach a file path to an error that does not carry one yet
+    pub fn with_path<P: AsRef<Path>>(mut self, file_path: P) -> Self {
+        match &mut self {
+            NeatifyError::Lex { path, .. }
+            | NeatifyError::Syntax { path, .. }
+            | NeatifyError::Io { path, .. }
+            | NeatifyError::Formatting { path, .. } => {
+                if path.is_none() {
+                    *path = Some(file_path.as_ref().to_path_buf());
+                }
+            }
+            NeatifyError::UnsupportedFile { .. } | NeatifyError::Config { .. } => {}
+        }
+        self
+    }
+}
+
+/// Write the `path:line:column: ` prefix of an error message
+fn write_position(
+    f: &mut fmt::Formatter<'_>,
+    path: Option<&Path>,
+    location: Option<Location>,
+) -> fmt::Result {
+    match (path, location) {
+        (Some(path), Some(location)) => write!(f, "{}:{}: ", path.display(), location),
+        (Some(path), None) => write!(f, "{}: ", path.display()),
+        (None, Some(location)) => write!(f, "{}: ", location),
+        (None, None) => Ok(()),
+    }
 }
 
 impl fmt::Display for NeatifyError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
-            NeatifyError::UnsupportedFile(path) => write!(f, "Unsupported file: {}", path),
-            NeatifyError::IoError(err) => write!(f, "IO error: {}", err),
-            NeatifyError::FormattingError(msg) => write!(f, "Formatting error: {}", msg),
+            NeatifyError::Lex { path, error } => {
+                write!(f, "Lex error: ")?;
+                write_position(f, path.as_deref(), Some(error.location))?;
+                write!(f, "{}", error.kind)
+            }
+            NeatifyError::Syntax {
+                path,
+                location,
+                message,
+            } => {
+                write!(f, "Syntax error: ")?;
+                write_position(f, path.as_deref(), Some(*location))?;
+                write!(f, "{}", message)
+            }
+            NeatifyError::UnsupportedFile { path } => {
+                write!(f, "Unsupported file: {}", path.display())
+            }
+            NeatifyError::Io { path, source } => {
+                write!(f, "IO error: ")?;
+                write_position(f, path.as_deref(), None)?;
+                write!(f, "{}", source)
+            }
+            NeatifyError::Config {
+                path,
+                location,
+                message,
+            } => {
+                write!(f, "Config error: ")?;
+                write_position(f, Some(path), *location)?;
+                write!(f, "{}", message)
+            }
+            NeatifyError::Formatting {
+                path,
+                location,
+                message,
+            } => {
+                write!(f, "Formatting error: ")?;
+                write_position(f, path.as_deref(), *location)?;
+                write!(f, "{}", message)
+            }
+        }
+    }
+}
+
+impl Clone for NeatifyError {
+    fn clone(&self) -> Self {
+        match self {
+            NeatifyError::Lex { path, error } => NeatifyError::Lex {
+                path: path.clone(),
+                error: *error,
+            },
+            NeatifyError::Syntax {
+                path,
+                location,
+                message,
+            } => NeatifyError::Syntax {
+                path: path.clone(),
+                location: *location,
+                message: message.clone(),
+            },
+            NeatifyError::UnsupportedFile { path } => {
+                NeatifyError::UnsupportedFile { path: path.clone() }
+            }
+            // I/O errors cannot be cloned, so keep their kind and message
+            NeatifyError::Io { path, source } => NeatifyError::Io {
+                path: path.clone(),
+                source: std::io::Error::new(source.kind(), source.to_string()),
+            },
+            NeatifyError::Config {
+                path,
+                location,
+                message,
+            } => NeatifyError::Config {
+                path: path.clone(),
+                location: *location,
+                message: message.clone(),
+            },
+            NeatifyError::Formatting {
+                path,
+                location,
+                message,
+            } => NeatifyError::Formatting {
+                path: path.clone(),
+                location: *location,
+                message: message.clone(),
+            },
         }
     }
 }
 
-impl std::error::Error for NeatifyError {}
+impl std::error::Error for NeatifyError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            NeatifyError::Lex { error, .. } => Some(error),
+            NeatifyError::Io { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
 
 impl From<std::io::Error> for NeatifyError {
     fn from(err: std::io::Error) -> Self {
-        NeatifyError::IoError(err)
+        NeatifyError::Io {
+            path: None,
+            source: err,
+        }
+    }
+}
+
+impl From<LexError> for NeatifyError {
+    fn from(err: LexError) -> Self {
+        NeatifyError::Lex {
+            path: None,
+            error: err,
+        }
     }
 }
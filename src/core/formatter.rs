@@ -1,7 +1,7 @@
 //! Core formatter functionality
 
-use crate::core::error::NeatifyError;
-use anyhow::Result;
+use crate::core::error::{NeatifyError, Result};
+use crate::core::options::FormatOptions;
 use std::fs;
 use std::path::Path;
 
@@ -12,8 +12,12 @@ pub struct FormattingStats {
     pub formatted_files: usize,
     /// Number of files that needed formatting but were not modified (dry run)
     pub files_needing_formatting: usize,
+    /// Number of files that could not be formatted because they failed to parse
+    pub failed_files: usize,
     /// Total number of files processed
     pub total_files: usize,
+    /// Diagnostics for the files that failed to parse
+    pub errors: Vec<NeatifyError>,
 }
 
 impl FormattingStats {
@@ -26,14 +30,18 @@ impl FormattingStats {
     pub fn merge(&mut self, other: &FormattingStats) {
         self.formatted_files += other.formatted_files;
         self.files_needing_formatting += other.files_needing_formatting;
+        self.failed_files += other.failed_files;
         self.total_files += other.total_files;
+        self.errors.extend(other.errors.iter().cloned());
     }
 }
 
 /// Trait for language-specific formatters
 pub trait Formatter {
     /// Format content according to language-specific rules
-    fn format(&self, content: &str) -> String;
+    ///
+    /// Returns an error with a diagnostic if the content cannot be parsed.
+    fn format(&self, content: &str, options: &FormatOptions) -> Result<String>;
 
     /// Check if a file is supported by this formatter
     fn is_supported(&self, file_path: &Path) -> bool;
@@ -43,39 +51,54 @@ pub trait Formatter {
     /// # Arguments
     /// * `file_path` - Path to the file to format
     /// * `write` - Whether to write the formatted output back to the file
+    /// * `options` - Options controlling the output style
     ///
     /// # Returns
     /// * `Ok(bool)` - `true` if the file needed formatting, `false` if it was already formatted
     /// * `Err` - If formatting failed
-    fn format_file(&self, file_path: &Path, write: bool) -> Result<bool> {
-        // Check if file exists
-        if !file_path.exists() {
-            return Err(NeatifyError::IoError(std::io::Error::new(
-                std::io::ErrorKind::NotFound,
-                format!("File does not exist: {}", file_path.display()),
-            ))
-            .into());
-        }
-
+    fn format_file(&self, file_path: &Path, write: bool, options: &FormatOptions) -> Result<bool> {
         // Check if file is supported
-        if !self.is_supported(file_path) {
-            return Err(NeatifyError::UnsupportedFile(file_path.display().to_string()).into());
+        if file_path.exists() && !self.is_supported(file_path) {
+            return Err(NeatifyError::unsupported_file(file_path));
         }
 
-        // Read the original content
-        let content = fs::read_to_string(file_path)?;
+        rewrite_file(self, file_path, write, options)
+    }
+}
 
-        // Format the content
-        let formatted = self.format(&content);
+/// Format a file with `formatter` without checking if the formatter supports it
+///
+/// Used when the formatter was already selected for the file, for example by a
+/// `FormatterRegistry` matching its extension.
+pub(crate) fn rewrite_file<F: Formatter + ?Sized>(
+    formatter: &F,
+    file_path: &Path,
+    write: bool,
+    options: &FormatOptions,
+) -> Result<bool> {
+    // Check if file exists
+    if !file_path.exists() {
+        return Err(NeatifyError::io(
+            file_path,
+            std::io::Error::new(std::io::ErrorKind::NotFound, "File does not exist"),
+        ));
+    }
 
-        // Check if formatting changed the content
-        let needs_formatting = content != formatted;
+    // Read the original content
+    let content = fs::read_to_string(file_path).map_err(|e| NeatifyError::io(file_path, e))?;
 
-        // Write the formatted content back to the file if needed
-        if needs_formatting && write {
-            fs::write(file_path, formatted)?;
-        }
+    // Format the content
+    let formatted = formatter
+        .format(&content, options)
+        .map_err(|e| e.with_path(file_path))?;
 
-        Ok(needs_formatting)
+    // Check if formatting changed the content
+    let needs_formatting = content != formatted;
+
+    // Write the formatted content back to the file if needed
+    if needs_formatting && write {
+        fs::write(file_path, formatted).map_err(|e| NeatifyError::io(file_path, e))?;
     }
+
+    Ok(needs_formatting)
 }
@@ -1,6 +1,9 @@
 //! Core functionality for the neatify library
 
+pub mod config;
+pub mod doc;
 pub mod error;
 pub mod formatter;
+pub mod options;
 pub mod parser;
 pub mod tokens;
@@ -0,0 +1,145 @@
+//! Formatting options shared by all formatters
+
+use serde::{Deserialize, Serialize};
+
+/// Quote character used for string literals
+///
+/// Strings containing more of the preferred quote than of the other one keep the
+/// quote that needs fewer escapes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum QuoteStyle {
+    /// Prefer double quotes (`"`)
+    Double,
+    /// Prefer single quotes (`'`)
+    Single,
+    /// Keep the quotes each string is written with
+    Preserve,
+}
+
+impl QuoteStyle {
+    /// The preferred quote character for this style, or `None` if strings keep
+    /// their own quotes
+    pub fn as_char(self) -> Option<char> {
+        match self {
+            QuoteStyle::Double => Some('"'),
+            QuoteStyle::Single => Some('\''),
+            QuoteStyle::Preserve => None,
+        }
+    }
+}
+
+/// Trailing comma policy for multi-line lists
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TrailingCommas {
+    /// Never print trailing commas and remove existing ones
+    None,
+    /// Print trailing commas where valid in ES5 (objects, arrays)
+    Es5,
+    /// Print trailing commas wherever the language allows them
+    All,
+}
+
+/// Placement of braces around blocks
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BraceStyle {
+    /// Opening braces end the header's line, and `else`, `catch`, `finally` and the
+    /// `while` of a `do` loop follow the closing brace (the "one true brace style")
+    #[serde(rename = "1tbs")]
+    OneTbs,
+    /// Like `OneTbs`, but `else`, `catch` and `finally` start a line of their own
+    Stroustrup,
+    /// Every non-empty block's opening brace goes on a line of its own, as do `else`,
+    /// `catch`, `finally` and `while`
+    Allman,
+}
+
+/// Line ending used in the formatted output
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EndOfLine {
+    /// Line feed only (`\n`)
+    Lf,
+    /// Carriage return and line feed (`\r\n`)
+    Crlf,
+    /// Carriage return only (`\r`)
+    Cr,
+    /// Keep the line ending of the first line in the input
+    Auto,
+}
+
+impl EndOfLine {
+    /// Resolve the line ending to use for the given input
+    pub fn resolve(self, content: &str) -> &'static str {
+        match self {
+            EndOfLine::Lf => "\n",
+            EndOfLine::Crlf => "\r\n",
+            EndOfLine::Cr => "\r",
+            EndOfLine::Auto => match content.find(['\r', '\n']) {
+                Some(index) if content[index..].starts_with("\r\n") => "\r\n",
+                Some(index) if content[index..].starts_with('\r') => "\r",
+                _ => "\n",
+            },
+        }
+    }
+}
+
+/// Options controlling the output style of a formatter
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FormatOptions {
+    /// Number of columns per indentation level
+    pub indent_width: usize,
+    /// Indent with tabs instead of spaces
+    pub use_tabs: bool,
+    /// Quote character for string literals
+    pub quote_style: QuoteStyle,
+    /// Line width the formatter tries to stay within
+    pub print_width: usize,
+    /// Print semicolons at the end of statements. Without them, a `;` is still printed
+    /// where automatic semicolon insertion would join two lines.
+    pub semicolons: bool,
+    /// Trailing comma policy for multi-line lists
+    pub trailing_commas: TrailingCommas,
+    /// Line ending used in the output
+    pub end_of_line: EndOfLine,
+    /// Rewrite numeric literals in a canonical form: lowercase prefixes and
+    /// exponents, uppercase hex digits and no redundant trailing zeros
+    pub normalize_numbers: bool,
+    /// Placement of braces around blocks
+    pub brace_style: BraceStyle,
+}
+
+impl Default for FormatOptions {
+    fn default() -> Self {
+        Self {
+            indent_width: 2,
+            use_tabs: false,
+            quote_style: QuoteStyle::Double,
+            print_width: 80,
+            semicolons: true,
+            trailing_commas: TrailingCommas::None,
+            end_of_line: EndOfLine::Lf,
+            normalize_numbers: false,
+            brace_style: BraceStyle::OneTbs,
+        }
+    }
+}
+
+impl FormatOptions {
+    /// Create the default options
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The string used for one level of indentation
+    pub fn indent_unit(&self) -> String {
+        if self.use_tabs {
+            "\t".to_string()
+        } else {
+            " ".repeat(self.indent_width)
+        }
+    }
+}
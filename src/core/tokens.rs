@@ -1,8 +1,20 @@
 //! Token definitions for code formatting
 
+use crate::core::error::{LexError, Location};
+
+/// Check if a character ends a line: LF, CR, LINE SEPARATOR or PARAGRAPH SEPARATOR
+pub(crate) fn is_line_terminator(c: char) -> bool {
+    matches!(c, '\n' | '\r' | '\u{2028}' | '\u{2029}')
+}
+
+/// Count the line breaks in `text`, where `\r\n` is a single line break
+pub(crate) fn line_break_count(text: &str) -> usize {
+    text.matches(is_line_terminator).count() - text.matches("\r\n").count()
+}
+
 /// Token types for code formatting
 #[derive(Debug, PartialEq, Clone)]
-pub enum Token {
+pub enum TokenKind {
     OpenBrace,
     CloseBrace,
     OpenParen,
@@ -13,24 +25,225 @@ pub enum Token {
     Colon,
     Comma,
     Dot,
+    /// `...` of spread elements and rest parameters
+    Ellipsis,
+    /// `?.` of optional chaining
+    QuestionDot,
+    /// A private class member name including its `#`, such as `#count`
+    PrivateName(String),
     Operator(String),
     Keyword(String),
     Identifier(String),
     StringLiteral(String),
+    /// A regular expression literal with its raw source text, such as `/ab+c/gi`
+    RegexLiteral(String),
+    /// A piece of a template literal with its raw text between the delimiters
+    Template(TemplateKind, String),
     NumberLiteral(String),
-    Comment(String),
+    Comment(CommentKind, String),
     Whitespace(String),
     Newline,
     Other(char),
+    /// End of the input, only present in lossless token streams
+    EndOfFile,
 }
 
-/// Trait for language-specific tokenizers
-pub trait Tokenizer: std::panic::RefUnwindSafe {
-    /// Tokenize source code into tokens
-    fn tokenize(&self, content: &str) -> Vec<Token>;
+impl TokenKind {
+    /// Check if this is whitespace, a line break or a comment
+    pub fn is_trivia(&self) -> bool {
+        matches!(
+            self,
+            TokenKind::Whitespace(_) | TokenKind::Newline | TokenKind::Comment(..)
+        )
+    }
+}
+
+/// Syntax of a comment
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum CommentKind {
+    /// A comment running to the end of the line, such as `// text`
+    Line,
+    /// A delimited comment, such as `/* text */`
+    Block,
+    /// A `#!` line at the very start of a file, such as `#!/usr/bin/env node`
+    Hashbang,
+}
+
+/// Position of a piece of a template literal among its substitutions
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum TemplateKind {
+    /// A whole template without substitutions, such as `` `text` ``
+    NoSubstitution,
+    /// The start of a template up to its first substitution, such as `` `text${ ``
+    Head,
+    /// The text between two substitutions, such as `}text${`
+    Middle,
+    /// The end of a template after its last substitution, such as `` }text` ``
+    Tail,
+}
+
+/// A range of source code
+#[derive(Debug, Default, PartialEq, Eq, Hash, Clone, Copy)]
+pub struct Span {
+    /// Byte offset of the first character
+    pub start: usize,
+    /// Byte offset just past the last character
+    pub end: usize,
+    /// 1-based line of the first character
+    pub line: usize,
+    /// 1-based column of the first character, counted in characters
+    pub column: usize,
+}
+
+impl Span {
+    /// The location of the start of the span
+    pub fn location(&self) -> Location {
+        Location {
+            offset: self.start,
+            line: self.line,
+            column: self.column,
+        }
+    }
+
+    /// The source text covered by the span
+    pub fn text<'a>(&self, content: &'a str) -> &'a str {
+        &content[self.start..self.end]
+    }
 }
 
-/// Parse source code into tokens
-pub fn parse(content: &str, tokenizer: &dyn Tokenizer) -> Vec<Token> {
-    tokenizer.tokenize(content)
+/// A token together with its position in the source
+///
+/// In a lossless token stream, whitespace, line breaks and comments are attached to
+/// the surrounding tokens as trivia instead of appearing as tokens of their own.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Token {
+    /// What the token is
+    pub kind: TokenKind,
+    /// Where the token is in the source
+    pub span: Span,
+    /// The exact source text of the token
+    pub text: String,
+    /// Trivia between the previous token's trailing trivia and this token
+    pub leading_trivia: Vec<Token>,
+    /// Whitespace and comments after this token up to the end of its line
+    pub trailing_trivia: Vec<Token>,
+}
+
+impl Token {
+    /// Create a new token without trivia
+    pub fn new(kind: TokenKind, span: Span, text: impl Into<String>) -> Self {
+        Self {
+            kind,
+            span,
+            text: text.into(),
+            leading_trivia: Vec::new(),
+            trailing_trivia: Vec::new(),
+        }
+    }
+
+    /// Number of line breaks in the leading trivia
+    pub fn newlines_before(&self) -> usize {
+        self.leading_trivia
+            .iter()
+            .filter(|trivia| trivia.kind == TokenKind::Newline)
+            .count()
+    }
+
+    /// Check if the leading trivia contains an empty line
+    pub fn has_blank_line_before(&self) -> bool {
+        let mut newlines = 0;
+        for trivia in &self.leading_trivia {
+            match trivia.kind {
+                TokenKind::Newline => newlines += 1,
+                TokenKind::Whitespace(_) => {}
+                _ => newlines = 0,
+            }
+            if newlines >= 2 {
+                return true;
+            }
+        }
+        false
+    }
+
+    /// Write the source text of the token including its trivia
+    pub fn write_source(&self, output: &mut String) {
+        for trivia in &self.leading_trivia {
+            output.push_str(&trivia.text);
+        }
+        output.push_str(&self.text);
+        for trivia in &self.trailing_trivia {
+            output.push_str(&trivia.text);
+        }
+    }
+}
+
+/// Attach trivia tokens to their neighbouring tokens
+///
+/// Whitespace and comments following a token on the same line become its trailing
+/// trivia; everything else becomes the leading trivia of the next token. The result
+/// ends with an `EndOfFile` token holding the trivia at the end of the input, so
+/// writing every token with `Token::write_source` reproduces the input exactly.
+pub fn attach_trivia(tokens: Vec<Token>) -> Vec<Token> {
+    let mut result: Vec<Token> = Vec::new();
+    let mut pending: Vec<Token> = Vec::new();
+    // Trivia belongs to the previous token until the first line break
+    let mut on_previous_line = false;
+
+    for token in tokens {
+        if token.kind.is_trivia() {
+            if token.kind == TokenKind::Newline {
+                on_previous_line = false;
+            }
+            match result.last_mut() {
+                Some(previous) if on_previous_line => previous.trailing_trivia.push(token),
+                _ => pending.push(token),
+            }
+        } else {
+            let mut token = token;
+            token.leading_trivia = std::mem::take(&mut pending);
+            result.push(token);
+            on_previous_line = true;
+        }
+    }
+
+    // The last piece of source text, to position the end of file after it
+    let last = pending.last().or_else(|| {
+        result
+            .last()
+            .and_then(|token| token.trailing_trivia.last().or(Some(token)))
+    });
+    let end = last.map_or(0, |token| token.span.end);
+    let (line, column) = last.map_or((1, 1), |token| {
+        let lines = line_break_count(&token.text);
+        match token.text.rsplit(is_line_terminator).next() {
+            Some(rest) if lines > 0 => (token.span.line + lines, rest.chars().count() + 1),
+            _ => (
+                token.span.line,
+                token.span.column + token.text.chars().count(),
+            ),
+        }
+    });
+
+    let mut eof = Token::new(
+        TokenKind::EndOfFile,
+        Span {
+            start: end,
+            end,
+            line,
+            column,
+        },
+        "",
+    );
+    eof.leading_trivia = pending;
+    result.push(eof);
+    result
+}
+
+/// Trait for language-specific tokenizers
+pub trait Tokenizer {
+    /// Tokenize source code into tokens
+    ///
+    /// Returns a positioned error for input that cannot be tokenized, such as an
+    /// unterminated string or comment.
+    fn tokenize(&self, content: &str) -> Result<Vec<Token>, LexError>;
 }
@@ -0,0 +1,386 @@
+//! JavaScript syntax tree
+//!
+//! Patterns (destructuring targets, parameters) are represented as expressions,
+//! since they share their syntax with object and array literals. Parentheses written
+//! in the source are kept as `ExpressionKind::Parenthesized` nodes.
+
+use crate::core::tokens::Span;
+
+/// A whole script or module
+#[derive(Debug, Clone)]
+pub struct Program {
+    pub body: Vec<Statement>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Statement {
+    pub kind: StatementKind,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone)]
+pub enum StatementKind {
+    Expression(Expression),
+    VariableDeclaration(VariableDeclaration),
+    FunctionDeclaration(Function),
+    ClassDeclaration(Class),
+    Return(Option<Expression>),
+    If {
+        test: Expression,
+        consequent: Box<Statement>,
+        alternate: Option<Box<Statement>>,
+    },
+    For {
+        init: Option<ForInit>,
+        test: Option<Expression>,
+        update: Option<Expression>,
+        body: Box<Statement>,
+    },
+    ForIn {
+        left: ForInit,
+        right: Expression,
+        body: Box<Statement>,
+    },
+    ForOf {
+        is_await: bool,
+        left: ForInit,
+        right: Expression,
+        body: Box<Statement>,
+    },
+    While {
+        test: Expression,
+        body: Box<Statement>,
+    },
+    DoWhile {
+        body: Box<Statement>,
+        test: Expression,
+    },
+    /// A `with` statement, only allowed in scripts
+    With {
+        object: Expression,
+        body: Box<Statement>,
+    },
+    Block(Block),
+    Empty,
+    Break(Option<String>),
+    Continue(Option<String>),
+    Throw(Expression),
+    Try {
+        block: Block,
+        handler: Option<CatchClause>,
+        finalizer: Option<Block>,
+    },
+    Switch {
+        discriminant: Expression,
+        cases: Vec<SwitchCase>,
+    },
+    Labeled {
+        label: String,
+        body: Box<Statement>,
+    },
+    Import(ImportDeclaration),
+    Export(ExportDeclaration),
+    Debugger,
+}
+
+/// A braced list of statements
+#[derive(Debug, Clone)]
+pub struct Block {
+    pub body: Vec<Statement>,
+    pub span: Span,
+}
+
+/// The initializer of a `for` statement header
+#[derive(Debug, Clone)]
+pub enum ForInit {
+    VariableDeclaration(VariableDeclaration),
+    Expression(Expression),
+}
+
+#[derive(Debug, Clone)]
+pub struct VariableDeclaration {
+    /// `var`, `let` or `const`
+    pub kind: String,
+    pub declarations: Vec<VariableDeclarator>,
+}
+
+#[derive(Debug, Clone)]
+pub struct VariableDeclarator {
+    pub id: Expression,
+    pub init: Option<Expression>,
+}
+
+#[derive(Debug, Clone)]
+pub struct CatchClause {
+    pub param: Option<Expression>,
+    pub body: Block,
+}
+
+#[derive(Debug, Clone)]
+pub struct SwitchCase {
+    /// The `case` expression, or `None` for `default`
+    pub test: Option<Expression>,
+    pub consequent: Vec<Statement>,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone)]
+pub struct ImportDeclaration {
+    pub default: Option<String>,
+    pub namespace: Option<String>,
+    /// Named imports, `None` when there are no braces
+    pub named: Option<Vec<ModuleSpecifier>>,
+    /// Raw source text of the module string
+    pub source: String,
+    pub span: Span,
+}
+
+/// An `a as b` pair in an import or export list
+#[derive(Debug, Clone)]
+pub struct ModuleSpecifier {
+    pub name: String,
+    pub alias: Option<String>,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone)]
+pub enum ExportDeclaration {
+    /// `export { a, b as c }` with an optional `from` source
+    Named {
+        specifiers: Vec<ModuleSpecifier>,
+        source: Option<String>,
+    },
+    /// `export * from "x"` or `export * as ns from "x"`
+    All {
+        alias: Option<String>,
+        source: String,
+    },
+    /// `export const a = 1`, `export function f() {}`, ...
+    Declaration(Box<Statement>),
+    /// `export default function () {}`, `export default class {}`
+    DefaultDeclaration(Box<Statement>),
+    /// `export default expression`
+    DefaultExpression(Expression),
+}
+
+#[derive(Debug, Clone)]
+pub struct Function {
+    pub name: Option<String>,
+    pub is_async: bool,
+    pub is_generator: bool,
+    pub params: Vec<Expression>,
+    pub body: Block,
+}
+
+#[derive(Debug, Clone)]
+pub struct ArrowFunction {
+    pub is_async: bool,
+    pub params: Vec<Expression>,
+    /// Whether the parameters are wrapped in parentheses
+    pub parenthesized: bool,
+    /// End offset of the parameters, including the closing parenthesis
+    pub params_end: usize,
+    pub body: ArrowBody,
+}
+
+#[derive(Debug, Clone)]
+pub enum ArrowBody {
+    Expression(Box<Expression>),
+    Block(Block),
+}
+
+#[derive(Debug, Clone)]
+pub struct Class {
+    pub name: Option<String>,
+    pub super_class: Option<Box<Expression>>,
+    pub body: Vec<ClassMember>,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone)]
+pub struct ClassMember {
+    pub kind: ClassMemberKind,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone)]
+pub enum ClassMemberKind {
+    Method {
+        is_static: bool,
+        kind: MethodKind,
+        key: PropertyKey,
+        function: Function,
+    },
+    Field {
+        is_static: bool,
+        key: PropertyKey,
+        value: Option<Expression>,
+    },
+    StaticBlock(Block),
+    /// A stray `;` in a class body
+    Empty,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MethodKind {
+    Method,
+    Getter,
+    Setter,
+    Constructor,
+}
+
+#[derive(Debug, Clone)]
+pub enum PropertyKey {
+    Identifier(String),
+    /// Raw source text of a string key
+    String(String),
+    /// Raw source text of a numeric key
+    Number(String),
+    /// A `#name` of a private class member
+    Private(String),
+    Computed(Box<Expression>),
+}
+
+#[derive(Debug, Clone)]
+pub struct Property {
+    pub kind: PropertyKind,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone)]
+pub enum PropertyKind {
+    /// `key: value`
+    KeyValue { key: PropertyKey, value: Expression },
+    /// `name` or, in patterns, `name = default`
+    Shorthand(Expression),
+    /// `key() {}`, `get key() {}`, `async *key() {}`, ...
+    Method {
+        kind: MethodKind,
+        key: PropertyKey,
+        function: Function,
+    },
+    /// `...expression`
+    Spread(Expression),
+}
+
+#[derive(Debug, Clone)]
+pub struct Expression {
+    pub kind: ExpressionKind,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone)]
+pub enum ExpressionKind {
+    Identifier(String),
+    /// Numbers, regular expressions, booleans and `null`, as written in the source
+    Literal(String),
+    /// A string literal with its raw source text
+    String(String),
+    /// A template literal
+    Template {
+        /// Raw source text of the pieces around the substitutions, including the
+        /// backticks and `${` `}` delimiters, one more than there are expressions
+        quasis: Vec<String>,
+        expressions: Vec<Expression>,
+    },
+    /// A template literal tagged with a function, as in ``html`<p>` ``
+    TaggedTemplate {
+        tag: Box<Expression>,
+        quasi: Box<Expression>,
+    },
+    This,
+    Super,
+    /// Array literal or pattern, `None` elements are holes
+    Array(Vec<Option<Expression>>),
+    Object(Vec<Property>),
+    Function(Box<Function>),
+    ArrowFunction(Box<ArrowFunction>),
+    Class(Box<Class>),
+    Unary {
+        operator: String,
+        argument: Box<Expression>,
+    },
+    Update {
+        operator: String,
+        prefix: bool,
+        argument: Box<Expression>,
+    },
+    Binary {
+        operator: String,
+        left: Box<Expression>,
+        right: Box<Expression>,
+    },
+    Assignment {
+        operator: String,
+        left: Box<Expression>,
+        right: Box<Expression>,
+    },
+    Conditional {
+        test: Box<Expression>,
+        consequent: Box<Expression>,
+        alternate: Box<Expression>,
+    },
+    Call {
+        callee: Box<Expression>,
+        arguments: Vec<Expression>,
+        /// Called with `?.()`
+        optional: bool,
+    },
+    New {
+        callee: Box<Expression>,
+        /// `None` for `new Foo` without parentheses
+        arguments: Option<Vec<Expression>>,
+    },
+    Member {
+        object: Box<Expression>,
+        property: Box<Expression>,
+        computed: bool,
+        /// Accessed with `?.`
+        optional: bool,
+    },
+    /// `new.target` and `import.meta`
+    MetaProperty {
+        meta: String,
+        property: String,
+    },
+    Sequence(Vec<Expression>),
+    Spread(Box<Expression>),
+    Yield {
+        argument: Option<Box<Expression>>,
+        delegate: bool,
+    },
+    Await(Box<Expression>),
+    Parenthesized(Box<Expression>),
+}
+
+impl Expression {
+    pub fn new(kind: ExpressionKind, span: Span) -> Self {
+        Self { kind, span }
+    }
+}
+
+impl Statement {
+    pub fn new(kind: StatementKind, span: Span) -> Self {
+        Self { kind, span }
+    }
+}
+
+/// Binding power of a binary operator, shared by the parser and the printer
+pub fn binary_precedence(operator: &str) -> Option<u8> {
+    let precedence = match operator {
+        "??" => 1,
+        "||" => 2,
+        "&&" => 3,
+        "|" => 4,
+        "^" => 5,
+        "&" => 6,
+        "==" | "!=" | "===" | "!==" => 7,
+        "<" | ">" | "<=" | ">=" | "instanceof" | "in" => 8,
+        "<<" | ">>" | ">>>" => 9,
+        "+" | "-" => 10,
+        "*" | "/" | "%" => 11,
+        "**" => 12,
+        _ => return None,
+    };
+    Some(precedence)
+}
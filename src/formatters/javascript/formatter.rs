@@ -1,243 +1,112 @@
 //! JavaScript formatter implementation
 
+use super::parser::parse_program;
+use super::printer::print_program;
 use super::tokenizer::JavaScriptTokenizer;
+use crate::core::error::{NeatifyError, Result};
+use crate::core::options::FormatOptions;
 use crate::core::parser;
-use crate::core::tokens::Token;
+use crate::core::tokens::{attach_trivia, CommentKind, Token, TokenKind};
 
-/// Format JavaScript code
-pub(crate) fn format_javascript(content: &str) -> String {
-    let tokenizer = JavaScriptTokenizer::new();
-    let tokens = match parser::parse(content, &tokenizer) {
-        Ok(tokens) => tokens,
-        Err(_) => return content.to_string(), // Return original content on error
-    };
-    let formatted = format_tokens(&tokens);
+/// Byte order mark, kept at the start of the output if the input has one
+const BOM: &str = "\u{feff}";
 
-    let normalized_content = normalize_whitespace(content);
-    let normalized_formatted = normalize_whitespace(&formatted);
-
-    if normalized_content == normalized_formatted {
-        return content.to_string();
-    }
+/// Stack size of the thread formatting runs on. Parsing and printing recurse into
+/// nested code, and input at the parser's nesting limit needs about a quarter of this
+/// in unoptimized builds.
+#[cfg(not(target_family = "wasm"))]
+const STACK_SIZE: usize = 128 * 1024 * 1024;
 
-    formatted
+/// Format JavaScript code
+pub(crate) fn format_javascript(content: &str, options: &FormatOptions) -> Result<String> {
+    with_stack(|| format_on_stack(content, options))
 }
 
-/// Normalize whitespace for comparison
-fn normalize_whitespace(s: &str) -> String {
-    // Replace multiple whitespace with a single space
-    let mut result = String::new();
-    let mut last_was_whitespace = false;
-
-    for c in s.chars() {
-        if c.is_whitespace() {
-            if !last_was_whitespace {
-                result.push(' ');
-                last_was_whitespace = true;
-            }
-        } else {
-            result.push(c);
-            last_was_whitespace = false;
-        }
-    }
-
-    result.trim().to_string()
+/// Run `format` on a thread with a stack of `STACK_SIZE`, as the caller's thread may
+/// have a small one
+#[cfg(not(target_family = "wasm"))]
+fn with_stack<T: Send>(format: impl FnOnce() -> Result<T> + Send) -> Result<T> {
+    std::thread::scope(|scope| {
+        let thread = std::thread::Builder::new()
+            .stack_size(STACK_SIZE)
+            .spawn_scoped(scope, format)?;
+        thread
+            .join()
+            .unwrap_or_else(|panic| std::panic::resume_unwind(panic))
+    })
 }
 
-/// Format tokens into a string
-fn format_tokens(tokens: &[Token]) -> String {
-    let mut result = String::new();
-    let mut indent_level = 0;
-    let mut at_line_start = true;
-
-    for (i, token) in tokens.iter().enumerate() {
-        match token {
-            Token::OpenBrace => {
-                // Add space before brace if not at line start and not already preceded by a space
-                if !at_line_start && !result.ends_with(' ') {
-                    result.push(' ');
-                }
-                result.push('{');
-                indent_level += 1;
-
-                // Add newline after opening brace
-                result.push('\n');
-                at_line_start = true;
-            }
-            Token::CloseBrace => {
-                // Add newline before closing brace if not at line start
-                if !at_line_start {
-                    result.push('\n');
-                }
-
-                // Decrease indent level
-                if indent_level > 0 {
-                    indent_level -= 1;
-                }
-
-                // Add indentation
-                for _ in 0..indent_level {
-                    result.push_str("  ");
-                }
-
-                result.push('}');
-
-                // Add newline after closing brace unless followed by specific tokens
-                let next_token = tokens.get(i + 1);
-                if !matches!(
-                    next_token,
-                    Some(Token::Semicolon) | Some(Token::Comma) | Some(Token::CloseParen)
-                ) {
-                    result.push('\n');
-                    at_line_start = true;
-                } else {
-                    at_line_start = false;
-                }
-            }
-            Token::OpenParen => {
-                result.push('(');
-                at_line_start = false;
-            }
-            Token::CloseParen => {
-                result.push(')');
-                at_line_start = false;
-            }
-            Token::OpenBracket => {
-                result.push('[');
-                at_line_start = false;
-            }
-            Token::CloseBracket => {
-                result.push(']');
-                at_line_start = false;
-            }
-            Token::Semicolon => {
-                result.push(';');
-
-                // Add newline after semicolon
-                result.push('\n');
-                at_line_start = true;
-            }
-            Token::Colon => {
-                result.push(':');
-                result.push(' '); // Add space after colon
-                at_line_start = false;
-            }
-            Token::Comma => {
-                result.push(',');
-                result.push(' '); // Add space after comma
-                at_line_start = false;
-            }
-            Token::Dot => {
-                result.push('.');
-                at_line_start = false;
-            }
-            Token::Operator(op) => {
-                // Add space before operator unless it's a unary operator
-                let prev_token = if i > 0 { tokens.get(i - 1) } else { None };
-                let is_unary = matches!(
-                    prev_token,
-                    Some(Token::OpenParen)
-                        | Some(Token::OpenBrace)
-                        | Some(Token::OpenBracket)
-                        | Some(Token::Comma)
-                        | Some(Token::Semicolon)
-                        | Some(Token::Colon)
-                        | Some(Token::Operator(_))
-                        | None
-                );
-
-                if !is_unary && !at_line_start {
-                    result.push(' ');
-                }
-
-                result.push_str(op);
-
-                // Add space after operator
-                let next_token = tokens.get(i + 1);
-                if !matches!(
-                    next_token,
-                    Some(Token::Semicolon) | Some(Token::Comma) | Some(Token::CloseParen)
-                ) {
-                    result.push(' ');
-                }
-
-                at_line_start = false;
-            }
-            Token::Keyword(keyword) => {
-                // Add indentation at line start
-                if at_line_start {
-                    for _ in 0..indent_level {
-                        result.push_str("  ");
-                    }
-                }
-
-                result.push_str(keyword);
+/// WebAssembly has no threads, so `format` runs on the current stack
+#[cfg(target_family = "wasm")]
+fn with_stack<T>(format: impl FnOnce() -> Result<T>) -> Result<T> {
+    format()
+}
 
-                // Add space after keyword
-                let next_token = tokens.get(i + 1);
-                if !matches!(
-                    next_token,
-                    Some(Token::Semicolon) | Some(Token::Comma) | Some(Token::Dot)
-                ) {
-                    result.push(' ');
-                }
+fn format_on_stack(content: &str, options: &FormatOptions) -> Result<String> {
+    let tokenizer = JavaScriptTokenizer::new();
+    let tokens = attach_trivia(parser::parse(content, &tokenizer)?);
+    let program = parse_program(&tokens)?;
 
-                at_line_start = false;
-            }
-            Token::Identifier(ident) => {
-                // Add indentation at line start
-                if at_line_start {
-                    for _ in 0..indent_level {
-                        result.push_str("  ");
-                    }
-                }
+    let mut formatted = print_program(&program, content, &tokens, options);
+    check_comments(&tokens, &formatted, &tokenizer)?;
+    if content.starts_with(BOM) {
+        formatted.insert_str(0, BOM);
+    }
 
-                result.push_str(ident);
-                at_line_start = false;
-            }
-            Token::StringLiteral(s) => {
-                result.push('"');
-                result.push_str(s);
-                result.push('"');
-                at_line_start = false;
-            }
-            Token::NumberLiteral(n) => {
-                result.push_str(n);
-                at_line_start = false;
-            }
-            Token::Comment(c) => {
-                // Add indentation at line start
-                if at_line_start {
-                    for _ in 0..indent_level {
-                        result.push_str("  ");
-                    }
-                } else {
-                    result.push(' '); // Add space before inline comment
-                }
+    Ok(apply_end_of_line(
+        &formatted,
+        options.end_of_line.resolve(content),
+    ))
+}
 
-                result.push_str("//");
-                result.push_str(c);
-                at_line_start = false;
-            }
-            Token::Whitespace(_ws) => {
-                // Skip whitespace tokens - we'll add spaces where needed
-                // This prevents extra spaces from being added
-            }
-            Token::Newline => {
-                result.push('\n');
-                at_line_start = true;
-            }
-            Token::Other(c) => {
-                result.push(*c);
-                at_line_start = false;
+/// Check that `formatted` contains the comments of the source in their original
+/// order, so that a comment is never lost or turns the code after it into a comment
+fn check_comments(
+    tokens: &[Token],
+    formatted: &str,
+    tokenizer: &JavaScriptTokenizer,
+) -> Result<()> {
+    let formatted_tokens = parser::parse(formatted, tokenizer)?;
+    let mut formatted_comments = formatted_tokens.iter().filter_map(comment_text);
+
+    for token in tokens {
+        for trivia in token.leading_trivia.iter().chain(&token.trailing_trivia) {
+            let Some(comment) = comment_text(trivia) else {
+                continue;
+            };
+            if formatted_comments.next() != Some(comment) {
+                return Err(NeatifyError::Formatting {
+                    path: None,
+                    location: Some(trivia.span.location()),
+                    message: "Comment could not be kept in place".to_string(),
+                });
             }
         }
     }
 
-    // Ensure the file ends with a newline
-    if !result.ends_with('\n') {
-        result.push('\n');
+    match formatted_comments.next() {
+        Some(_) => Err(NeatifyError::Formatting {
+            path: None,
+            location: None,
+            message: "Formatting produced an unexpected comment".to_string(),
+        }),
+        None => Ok(()),
+    }
+}
+
+/// Kind and text of a comment without whitespace, which the formatter may re-indent
+fn comment_text(token: &Token) -> Option<(CommentKind, String)> {
+    match &token.kind {
+        TokenKind::Comment(kind, text) => Some((*kind, text.split_whitespace().collect())),
+        _ => None,
     }
+}
 
-    result
+/// Convert the `\n` line endings produced by the formatter to `eol`
+fn apply_end_of_line(formatted: &str, eol: &str) -> String {
+    if eol == "\n" {
+        formatted.to_string()
+    } else {
+        formatted.replace('\n', eol)
+    }
 }
@@ -1,9 +1,16 @@
 //! JavaScript formatter implementation
 
+mod ast;
 mod formatter;
+mod parser;
+mod printer;
 mod tokenizer;
 
+pub use tokenizer::JavaScriptTokenizer;
+
+use crate::core::error::Result;
 use crate::core::formatter::Formatter;
+use crate::core::options::FormatOptions;
 use std::path::Path;
 
 /// JavaScript formatter
@@ -17,8 +24,8 @@ impl JavaScriptFormatter {
 }
 
 impl Formatter for JavaScriptFormatter {
-    fn format(&self, content: &str) -> String {
-        formatter::format_javascript(content)
+    fn format(&self, content: &str, options: &FormatOptions) -> Result<String> {
+        formatter::format_javascript(content, options)
     }
 
     fn is_supported(&self, file_path: &Path) -> bool {
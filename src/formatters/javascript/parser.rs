@@ -0,0 +1,1771 @@
+//! Recursive descent parser for JavaScript
+//!
+//! The parser works on a lossless token stream (see `attach_trivia`), so comments and
+//! whitespace never get in the way, while line breaks are still visible in the trivia
+//! for automatic semicolon insertion.
+
+use super::ast::*;
+use crate::core::error::{NeatifyError, Result};
+use crate::core::tokens::{is_line_terminator, Span, TemplateKind, Token, TokenKind};
+
+/// Keywords that can also be used as identifiers
+const CONTEXTUAL_KEYWORDS: [&str; 5] = ["from", "as", "async", "let", "undefined"];
+
+/// Assignment operators
+const ASSIGNMENT_OPERATORS: [&str; 16] = [
+    "=", "+=", "-=", "*=", "/=", "%=", "**=", "<<=", ">>=", ">>>=", "&=", "|=", "^=", "&&=", "||=",
+    "??=",
+];
+
+/// Deepest nesting of statements, expressions and links of member, call and binary
+/// chains the parser accepts, so that deeply nested input is reported as an error
+/// instead of overflowing the stack while parsing and printing
+const MAX_DEPTH: usize = 1000;
+
+/// Parse a lossless token stream into a program
+///
+/// Scripts and modules share the `.js` extension, so the source is parsed as a module
+/// if it has `import` or `export` declarations, or if it only parses as one, such as
+/// a module using top-level `await`.
+pub(crate) fn parse_program(tokens: &[Token]) -> Result<Program> {
+    let module = has_module_declarations(tokens);
+    match Parser::new(tokens, module).parse_program() {
+        Err(script_error) if !module => {
+            Parser::new(tokens, true)
+                .parse_program()
+                .map_err(|module_error| {
+                    // Report the error of the parse that got further
+                    let offset =
+                        |error: &NeatifyError| error.location().map(|location| location.offset);
+                    if offset(&module_error) > offset(&script_error) {
+                        module_error
+                    } else {
+                        script_error
+                    }
+                })
+        }
+        result => result,
+    }
+}
+
+/// Check for `import` or `export` declarations at the top level of a token stream
+fn has_module_declarations(tokens: &[Token]) -> bool {
+    let mut depth = 0usize;
+    for (index, token) in tokens.iter().enumerate() {
+        match &token.kind {
+            TokenKind::OpenParen | TokenKind::OpenBracket | TokenKind::OpenBrace => depth += 1,
+            TokenKind::Template(TemplateKind::Head, _) => depth += 1,
+            TokenKind::CloseParen | TokenKind::CloseBracket | TokenKind::CloseBrace => {
+                depth = depth.saturating_sub(1)
+            }
+            TokenKind::Template(TemplateKind::Tail, _) => depth = depth.saturating_sub(1),
+            TokenKind::Keyword(keyword) if depth == 0 => {
+                let next = tokens.get(index + 1).map(|token| &token.kind);
+                let is_declaration = match keyword.as_str() {
+                    "export" => true,
+                    // Not `import(...)` or `import.meta`
+                    "import" => !matches!(next, Some(TokenKind::OpenParen | TokenKind::Dot)),
+                    _ => false,
+                };
+                let is_property = index > 0
+                    && matches!(
+                        tokens[index - 1].kind,
+                        TokenKind::Dot | TokenKind::QuestionDot
+                    );
+                if is_declaration && !is_property {
+                    return true;
+                }
+            }
+            _ => {}
+        }
+    }
+    false
+}
+
+/// Operators of prefix unary and update expressions
+const PREFIX_OPERATORS: [&str; 6] = ["!", "~", "+", "-", "++", "--"];
+
+struct Parser<'a> {
+    tokens: &'a [Token],
+    /// Index of the current token
+    pos: usize,
+    /// Whether `in` is not a binary operator, as in a `for` statement initializer
+    no_in: bool,
+    /// Whether the source is a module, where `await` and `yield` are always reserved
+    module: bool,
+    /// Whether `await` is an operator, in an async function or at the top level of a module
+    in_async: bool,
+    /// Whether `yield` is an operator, in a generator function
+    in_generator: bool,
+    /// Nesting depth of the statement or expression being parsed
+    depth: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn new(tokens: &'a [Token], module: bool) -> Self {
+        Self {
+            tokens,
+            pos: 0,
+            no_in: false,
+            module,
+            in_async: module,
+            in_generator: false,
+            depth: 0,
+        }
+    }
+
+    fn parse_program(mut self) -> Result<Program> {
+        let mut body = Vec::new();
+        while !self.at(&TokenKind::EndOfFile) {
+            body.push(self.parse_statement()?);
+        }
+
+        Ok(Program { body })
+    }
+
+    // Token access
+
+    fn current(&self) -> &'a Token {
+        self.token_at(self.pos)
+    }
+
+    fn token_at(&self, index: usize) -> &'a Token {
+        // The stream always ends with an `EndOfFile` token
+        &self.tokens[index.min(self.tokens.len() - 1)]
+    }
+
+    fn advance(&mut self) -> &'a Token {
+        let token = self.current();
+        if token.kind != TokenKind::EndOfFile {
+            self.pos += 1;
+        }
+        token
+    }
+
+    fn at(&self, kind: &TokenKind) -> bool {
+        &self.current().kind == kind
+    }
+
+    fn at_index(&self, index: usize, kind: &TokenKind) -> bool {
+        &self.token_at(index).kind == kind
+    }
+
+    fn eat(&mut self, kind: &TokenKind) -> bool {
+        if self.at(kind) {
+            self.advance();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, kind: &TokenKind) -> Result<&'a Token> {
+        if self.at(kind) {
+            Ok(self.advance())
+        } else {
+            Err(self.unexpected())
+        }
+    }
+
+    fn is_op_at(&self, index: usize, operator: &str) -> bool {
+        matches!(&self.token_at(index).kind, TokenKind::Operator(op) if op == operator)
+    }
+
+    fn is_op(&self, operator: &str) -> bool {
+        self.is_op_at(self.pos, operator)
+    }
+
+    fn eat_op(&mut self, operator: &str) -> bool {
+        if self.is_op(operator) {
+            self.advance();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect_op(&mut self, operator: &str) -> Result<()> {
+        if self.eat_op(operator) {
+            Ok(())
+        } else {
+            Err(self.unexpected())
+        }
+    }
+
+    fn is_keyword_at(&self, index: usize, keyword: &str) -> bool {
+        matches!(&self.token_at(index).kind, TokenKind::Keyword(k) if k == keyword)
+    }
+
+    fn is_keyword(&self, keyword: &str) -> bool {
+        self.is_keyword_at(self.pos, keyword)
+    }
+
+    fn expect_keyword(&mut self, keyword: &str) -> Result<()> {
+        if self.is_keyword(keyword) {
+            self.advance();
+            Ok(())
+        } else {
+            Err(self.unexpected())
+        }
+    }
+
+    /// Check for an identifier or keyword spelled `word`, such as `of` or `get`
+    fn is_word(&self, word: &str) -> bool {
+        matches!(
+            &self.current().kind,
+            TokenKind::Identifier(_) | TokenKind::Keyword(_)
+        ) && self.current().text == word
+    }
+
+    fn expect_word(&mut self, word: &str) -> Result<()> {
+        if self.is_word(word) {
+            self.advance();
+            Ok(())
+        } else {
+            Err(self.unexpected())
+        }
+    }
+
+    /// Check if the token at `index` can be used as an identifier
+    fn is_identifier_at(&self, index: usize) -> bool {
+        match &self.token_at(index).kind {
+            TokenKind::Identifier(_) => true,
+            TokenKind::Keyword(keyword) => match keyword.as_str() {
+                "await" => !self.in_async && !self.module,
+                "yield" => !self.in_generator && !self.module,
+                keyword => CONTEXTUAL_KEYWORDS.contains(&keyword),
+            },
+            _ => false,
+        }
+    }
+
+    fn is_identifier(&self) -> bool {
+        self.is_identifier_at(self.pos)
+    }
+
+    /// Check if a line break separates the token at `index` from the previous one
+    fn newline_before_at(&self, index: usize) -> bool {
+        self.token_at(index)
+            .leading_trivia
+            .iter()
+            .any(|trivia| match &trivia.kind {
+                TokenKind::Newline => true,
+                TokenKind::Comment(_, text) => text.contains(is_line_terminator),
+                _ => false,
+            })
+    }
+
+    fn newline_before(&self) -> bool {
+        self.newline_before_at(self.pos)
+    }
+
+    /// The span from `start` to the end of the previous token
+    fn span_from(&self, start: Span) -> Span {
+        let end = self.pos.checked_sub(1).map_or(start.end, |previous| {
+            self.tokens[previous].span.end.max(start.start)
+        });
+        Span { end, ..start }
+    }
+
+    /// Error for the current token
+    fn unexpected(&self) -> NeatifyError {
+        let token = self.current();
+        let message = match token.kind {
+            TokenKind::EndOfFile => "Unexpected end of file".to_string(),
+            _ => format!("Unexpected token `{}`", token.text),
+        };
+        self.error(message)
+    }
+
+    /// Error at the current token
+    fn error(&self, message: String) -> NeatifyError {
+        NeatifyError::Syntax {
+            path: None,
+            location: self.current().span.location(),
+            message,
+        }
+    }
+
+    /// Index of the bracket closing the one at `open`
+    fn matching_close(&self, open: usize) -> Option<usize> {
+        let mut depth = 0usize;
+        for (index, token) in self.tokens.iter().enumerate().skip(open) {
+            match token.kind {
+                TokenKind::OpenParen | TokenKind::OpenBracket | TokenKind::OpenBrace => depth += 1,
+                TokenKind::CloseParen | TokenKind::CloseBracket | TokenKind::CloseBrace => {
+                    depth -= 1;
+                    if depth == 0 {
+                        return Some(index);
+                    }
+                }
+                _ => {}
+            }
+        }
+        None
+    }
+
+    /// Check if an arrow function's parameters start at `index`
+    fn is_arrow_at(&self, index: usize) -> bool {
+        if self.is_identifier_at(index) {
+            return self.is_op_at(index + 1, "=>");
+        }
+        if self.at_index(index, &TokenKind::OpenParen) {
+            return self
+                .matching_close(index)
+                .is_some_and(|close| self.is_op_at(close + 1, "=>"));
+        }
+        false
+    }
+
+    /// Run `parse` with `in` allowed as a binary operator
+    fn allow_in<T>(&mut self, parse: impl FnOnce(&mut Self) -> Result<T>) -> Result<T> {
+        let no_in = std::mem::replace(&mut self.no_in, false);
+        let result = parse(self);
+        self.no_in = no_in;
+        result
+    }
+
+    /// Run `parse` with `in` not allowed as a binary operator
+    fn disallow_in<T>(&mut self, parse: impl FnOnce(&mut Self) -> Result<T>) -> Result<T> {
+        let no_in = std::mem::replace(&mut self.no_in, true);
+        let result = parse(self);
+        self.no_in = no_in;
+        result
+    }
+
+    /// Run `parse` inside a function, where `await` and `yield` are operators only if
+    /// the function is async or a generator
+    fn in_function<T>(
+        &mut self,
+        is_async: bool,
+        is_generator: bool,
+        parse: impl FnOnce(&mut Self) -> Result<T>,
+    ) -> Result<T> {
+        let in_async = std::mem::replace(&mut self.in_async, is_async);
+        let in_generator = std::mem::replace(&mut self.in_generator, is_generator);
+        let result = parse(self);
+        self.in_async = in_async;
+        self.in_generator = in_generator;
+        result
+    }
+
+    /// Run `parse` one nesting level deeper, failing if the input is nested too deeply
+    fn nested<T>(&mut self, parse: impl FnOnce(&mut Self) -> Result<T>) -> Result<T> {
+        self.chain(|parser| {
+            parser.deepen()?;
+            parse(parser)
+        })
+    }
+
+    /// Run `parse` for a chain that nests the syntax tree in a loop rather than by
+    /// recursion, such as `a.b().c` or `a + b + c`, going one level deeper with
+    /// `deepen` for each link
+    fn chain<T>(&mut self, parse: impl FnOnce(&mut Self) -> Result<T>) -> Result<T> {
+        let depth = self.depth;
+        let result = parse(self);
+        self.depth = depth;
+        result
+    }
+
+    /// Go one nesting level deeper, failing if the input is nested too deeply
+    fn deepen(&mut self) -> Result<()> {
+        if self.depth == MAX_DEPTH {
+            return Err(self.error("Nesting is too deep".to_string()));
+        }
+        self.depth += 1;
+        Ok(())
+    }
+
+    /// Consume a `;` or accept its automatic insertion
+    fn consume_semicolon(&mut self) -> Result<()> {
+        if self.eat(&TokenKind::Semicolon)
+            || self.at(&TokenKind::CloseBrace)
+            || self.at(&TokenKind::EndOfFile)
+            || self.newline_before()
+        {
+            Ok(())
+        } else {
+            Err(self.unexpected())
+        }
+    }
+
+    fn parse_identifier(&mut self) -> Result<String> {
+        if self.is_identifier() {
+            Ok(self.advance().text.clone())
+        } else {
+            Err(self.unexpected())
+        }
+    }
+
+    /// Parse a property or export name, which may be any word including keywords
+    fn parse_identifier_name(&mut self) -> Result<String> {
+        match self.current().kind {
+            TokenKind::Identifier(_) | TokenKind::Keyword(_) => Ok(self.advance().text.clone()),
+            _ => Err(self.unexpected()),
+        }
+    }
+
+    // Statements
+
+    fn parse_statement(&mut self) -> Result<Statement> {
+        self.nested(Self::parse_statement_inner)
+    }
+
+    fn parse_statement_inner(&mut self) -> Result<Statement> {
+        let start = self.current().span;
+        let kind = match &self.current().kind {
+            _ if self.is_identifier() && self.at_index(self.pos + 1, &TokenKind::Colon) => {
+                let label = self.parse_identifier()?;
+                self.advance();
+                StatementKind::Labeled {
+                    label,
+                    body: Box::new(self.parse_statement()?),
+                }
+            }
+            TokenKind::OpenBrace => StatementKind::Block(self.parse_block()?),
+            TokenKind::Semicolon => {
+                self.advance();
+                StatementKind::Empty
+            }
+            TokenKind::Identifier(name) if name == "debugger" => {
+                self.advance();
+                self.consume_semicolon()?;
+                StatementKind::Debugger
+            }
+            TokenKind::Keyword(keyword) => match keyword.as_str() {
+                "var" | "const" => self.parse_variable_statement()?,
+                "let" if self.is_let_declaration() => self.parse_variable_statement()?,
+                "function" => StatementKind::FunctionDeclaration(self.parse_function(false)?),
+                "async" if self.is_async_function() => {
+                    self.advance();
+                    StatementKind::FunctionDeclaration(self.parse_function(true)?)
+                }
+                "class" => StatementKind::ClassDeclaration(self.parse_class()?),
+                "if" => self.parse_if()?,
+                "for" => self.parse_for()?,
+                "while" => {
+                    self.advance();
+                    let test = self.parse_parenthesized()?;
+                    StatementKind::While {
+                        test,
+                        body: Box::new(self.parse_statement()?),
+                    }
+                }
+                "with" if !self.module => {
+                    self.advance();
+                    let object = self.parse_parenthesized()?;
+                    StatementKind::With {
+                        object,
+                        body: Box::new(self.parse_statement()?),
+                    }
+                }
+                "do" => {
+                    self.advance();
+                    let body = Box::new(self.parse_statement()?);
+                    self.expect_keyword("while")?;
+                    let test = self.parse_parenthesized()?;
+                    // The semicolon after a do-while loop is always optional
+                    self.eat(&TokenKind::Semicolon);
+                    StatementKind::DoWhile { body, test }
+                }
+                "return" => {
+                    self.advance();
+                    let argument = if self.can_insert_semicolon() {
+                        None
+                    } else {
+                        Some(self.parse_expression()?)
+                    };
+                    self.consume_semicolon()?;
+                    StatementKind::Return(argument)
+                }
+                "break" | "continue" => {
+                    let is_break = keyword == "break";
+                    self.advance();
+                    let label = if self.is_identifier() && !self.newline_before() {
+                        Some(self.parse_identifier()?)
+                    } else {
+                        None
+                    };
+                    self.consume_semicolon()?;
+                    if is_break {
+                        StatementKind::Break(label)
+                    } else {
+                        StatementKind::Continue(label)
+                    }
+                }
+                "throw" => {
+                    self.advance();
+                    if self.newline_before() {
+                        return Err(self.unexpected());
+                    }
+                    let argument = self.parse_expression()?;
+                    self.consume_semicolon()?;
+                    StatementKind::Throw(argument)
+                }
+                "try" => self.parse_try()?,
+                "switch" => self.parse_switch()?,
+                "import"
+                    if !matches!(
+                        self.token_at(self.pos + 1).kind,
+                        TokenKind::OpenParen | TokenKind::Dot
+                    ) =>
+                {
+                    StatementKind::Import(self.parse_import()?)
+                }
+                "export" => StatementKind::Export(self.parse_export()?),
+                _ => self.parse_expression_statement()?,
+            },
+            _ => self.parse_expression_statement()?,
+        };
+
+        Ok(Statement::new(kind, self.span_from(start)))
+    }
+
+    /// Check if the statement ends here, either explicitly or by semicolon insertion
+    fn can_insert_semicolon(&self) -> bool {
+        matches!(
+            self.current().kind,
+            TokenKind::Semicolon | TokenKind::CloseBrace | TokenKind::EndOfFile
+        ) || self.newline_before()
+    }
+
+    /// Check if `let` starts a declaration rather than an expression
+    fn is_let_declaration(&self) -> bool {
+        self.is_identifier_at(self.pos + 1)
+            || matches!(
+                self.token_at(self.pos + 1).kind,
+                TokenKind::OpenBracket | TokenKind::OpenBrace
+            )
+    }
+
+    /// Check for `async function` without a line break in between
+    fn is_async_function(&self) -> bool {
+        self.is_word("async")
+            && self.is_keyword_at(self.pos + 1, "function")
+            && !self.newline_before_at(self.pos + 1)
+    }
+
+    fn parse_expression_statement(&mut self) -> Result<StatementKind> {
+        let expression = self.parse_expression()?;
+        self.consume_semicolon()?;
+        Ok(StatementKind::Expression(expression))
+    }
+
+    fn parse_variable_statement(&mut self) -> Result<StatementKind> {
+        let declaration = self.parse_variable_declaration()?;
+        self.consume_semicolon()?;
+        Ok(StatementKind::VariableDeclaration(declaration))
+    }
+
+    fn parse_variable_declaration(&mut self) -> Result<VariableDeclaration> {
+        let kind = self.advance().text.clone();
+
+        let mut declarations = Vec::new();
+        loop {
+            let id = self.parse_binding_target()?;
+            let init = if self.eat_op("=") {
+                Some(self.parse_assignment()?)
+            } else {
+                None
+            };
+            declarations.push(VariableDeclarator { id, init });
+
+            if !self.eat(&TokenKind::Comma) {
+                break;
+            }
+        }
+
+        Ok(VariableDeclaration { kind, declarations })
+    }
+
+    /// Parse an identifier or a destructuring pattern
+    fn parse_binding_target(&mut self) -> Result<Expression> {
+        match self.current().kind {
+            TokenKind::OpenBrace => self.parse_object(),
+            TokenKind::OpenBracket => self.parse_array(),
+            _ => {
+                let start = self.current().span;
+                let name = self.parse_identifier()?;
+                Ok(Expression::new(
+                    ExpressionKind::Identifier(name),
+                    self.span_from(start),
+                ))
+            }
+        }
+    }
+
+    fn parse_block(&mut self) -> Result<Block> {
+        let start = self.current().span;
+        self.expect(&TokenKind::OpenBrace)?;
+
+        let mut body = Vec::new();
+        while !self.eat(&TokenKind::CloseBrace) {
+            body.push(self.parse_statement()?);
+        }
+
+        Ok(Block {
+            body,
+            span: self.span_from(start),
+        })
+    }
+
+    /// Parse an expression in parentheses, as in `if (test)`
+    fn parse_parenthesized(&mut self) -> Result<Expression> {
+        self.expect(&TokenKind::OpenParen)?;
+        let expression = self.allow_in(Self::parse_expression)?;
+        self.expect(&TokenKind::CloseParen)?;
+        Ok(expression)
+    }
+
+    fn parse_if(&mut self) -> Result<StatementKind> {
+        self.advance();
+        let test = self.parse_parenthesized()?;
+        let consequent = Box::new(self.parse_statement()?);
+        let alternate = if self.is_keyword("else") {
+            self.advance();
+            Some(Box::new(self.parse_statement()?))
+        } else {
+            None
+        };
+
+        Ok(StatementKind::If {
+            test,
+            consequent,
+            alternate,
+        })
+    }
+
+    fn parse_for(&mut self) -> Result<StatementKind> {
+        self.advance();
+        let is_await = self.in_async && self.is_keyword("await");
+        if is_await {
+            self.advance();
+        }
+        self.expect(&TokenKind::OpenParen)?;
+
+        let init = if self.at(&TokenKind::Semicolon) {
+            None
+        } else if self.is_keyword("var")
+            || self.is_keyword("const")
+            || (self.is_keyword("let") && self.is_let_declaration())
+        {
+            Some(ForInit::VariableDeclaration(
+                self.disallow_in(Self::parse_variable_declaration)?,
+            ))
+        } else {
+            Some(ForInit::Expression(
+                self.disallow_in(Self::parse_expression)?,
+            ))
+        };
+
+        if let Some(left) = init {
+            if self.is_keyword("in") {
+                self.advance();
+                let right = self.allow_in(Self::parse_expression)?;
+                self.expect(&TokenKind::CloseParen)?;
+                return Ok(StatementKind::ForIn {
+                    left,
+                    right,
+                    body: Box::new(self.parse_statement()?),
+                });
+            }
+            if self.is_word("of") {
+                self.advance();
+                let right = self.allow_in(Self::parse_assignment)?;
+                self.expect(&TokenKind::CloseParen)?;
+                return Ok(StatementKind::ForOf {
+                    is_await,
+                    left,
+                    right,
+                    body: Box::new(self.parse_statement()?),
+                });
+            }
+            return self.parse_for_rest(Some(left));
+        }
+
+        self.parse_for_rest(None)
+    }
+
+    /// Parse the rest of a `for (init; test; update)` statement after `init`
+    fn parse_for_rest(&mut self, init: Option<ForInit>) -> Result<StatementKind> {
+        self.expect(&TokenKind::Semicolon)?;
+        let test = if self.at(&TokenKind::Semicolon) {
+            None
+        } else {
+            Some(self.allow_in(Self::parse_expression)?)
+        };
+        self.expect(&TokenKind::Semicolon)?;
+        let update = if self.at(&TokenKind::CloseParen) {
+            None
+        } else {
+            Some(self.allow_in(Self::parse_expression)?)
+        };
+        self.expect(&TokenKind::CloseParen)?;
+
+        Ok(StatementKind::For {
+            init,
+            test,
+            update,
+            body: Box::new(self.parse_statement()?),
+        })
+    }
+
+    fn parse_try(&mut self) -> Result<StatementKind> {
+        self.advance();
+        let block = self.parse_block()?;
+
+        let handler = if self.is_keyword("catch") {
+            self.advance();
+            let param = if self.eat(&TokenKind::OpenParen) {
+                let param = self.parse_binding_target()?;
+                self.expect(&TokenKind::CloseParen)?;
+                Some(param)
+            } else {
+                None
+            };
+            let body = self.parse_block()?;
+            Some(CatchClause { param, body })
+        } else {
+            None
+        };
+
+        let finalizer = if self.is_keyword("finally") {
+            self.advance();
+            Some(self.parse_block()?)
+        } else {
+            None
+        };
+
+        if handler.is_none() && finalizer.is_none() {
+            return Err(self.unexpected());
+        }
+
+        Ok(StatementKind::Try {
+            block,
+            handler,
+            finalizer,
+        })
+    }
+
+    fn parse_switch(&mut self) -> Result<StatementKind> {
+        self.advance();
+        let discriminant = self.parse_parenthesized()?;
+        self.expect(&TokenKind::OpenBrace)?;
+
+        let mut cases = Vec::new();
+        while !self.eat(&TokenKind::CloseBrace) {
+            let start = self.current().span;
+            let test = if self.is_keyword("case") {
+                self.advance();
+                Some(self.allow_in(Self::parse_expression)?)
+            } else {
+                self.expect_keyword("default")?;
+                None
+            };
+            self.expect(&TokenKind::Colon)?;
+
+            let mut consequent = Vec::new();
+            while !self.at(&TokenKind::CloseBrace)
+                && !self.is_keyword("case")
+                && !self.is_keyword("default")
+            {
+                consequent.push(self.parse_statement()?);
+            }
+
+            cases.push(SwitchCase {
+                test,
+                consequent,
+                span: self.span_from(start),
+            });
+        }
+
+        Ok(StatementKind::Switch {
+            discriminant,
+            cases,
+        })
+    }
+
+    fn parse_import(&mut self) -> Result<ImportDeclaration> {
+        let start = self.current().span;
+        self.advance();
+
+        let mut declaration = ImportDeclaration {
+            default: None,
+            namespace: None,
+            named: None,
+            source: String::new(),
+            span: start,
+        };
+
+        if !matches!(self.current().kind, TokenKind::StringLiteral(_)) {
+            let mut has_more = true;
+            if self.is_identifier() {
+                declaration.default = Some(self.parse_identifier()?);
+                has_more = self.eat(&TokenKind::Comma);
+            }
+            if has_more {
+                if self.eat_op("*") {
+                    self.expect_word("as")?;
+                    declaration.namespace = Some(self.parse_identifier()?);
+                } else {
+                    declaration.named = Some(self.parse_module_specifiers()?);
+                }
+            }
+            self.expect_word("from")?;
+        }
+
+        declaration.source = self.parse_module_source()?;
+        self.consume_semicolon()?;
+        declaration.span = self.span_from(start);
+        Ok(declaration)
+    }
+
+    fn parse_export(&mut self) -> Result<ExportDeclaration> {
+        self.advance();
+
+        if self.is_keyword("default") {
+            self.advance();
+            if self.is_keyword("function") || self.is_keyword("class") || self.is_async_function() {
+                return Ok(ExportDeclaration::DefaultDeclaration(Box::new(
+                    self.parse_statement()?,
+                )));
+            }
+            let expression = self.parse_assignment()?;
+            self.consume_semicolon()?;
+            return Ok(ExportDeclaration::DefaultExpression(expression));
+        }
+
+        if self.eat_op("*") {
+            let alias = if self.is_word("as") {
+                self.advance();
+                Some(self.parse_identifier_name()?)
+            } else {
+                None
+            };
+            self.expect_word("from")?;
+            let source = self.parse_module_source()?;
+            self.consume_semicolon()?;
+            return Ok(ExportDeclaration::All { alias, source });
+        }
+
+        if self.at(&TokenKind::OpenBrace) {
+            let specifiers = self.parse_module_specifiers()?;
+            let source = if self.is_word("from") {
+                self.advance();
+                Some(self.parse_module_source()?)
+            } else {
+                None
+            };
+            self.consume_semicolon()?;
+            return Ok(ExportDeclaration::Named { specifiers, source });
+        }
+
+        let is_declaration = ["var", "let", "const", "function", "class"]
+            .iter()
+            .any(|keyword| self.is_keyword(keyword))
+            || self.is_async_function();
+        if !is_declaration {
+            return Err(self.unexpected());
+        }
+        Ok(ExportDeclaration::Declaration(Box::new(
+            self.parse_statement()?,
+        )))
+    }
+
+    /// Parse `{ a, b as c }` in an import or export declaration
+    fn parse_module_specifiers(&mut self) -> Result<Vec<ModuleSpecifier>> {
+        self.expect(&TokenKind::OpenBrace)?;
+
+        let mut specifiers = Vec::new();
+        while !self.eat(&TokenKind::CloseBrace) {
+            let start = self.current().span;
+            let name = match self.current().kind {
+                TokenKind::StringLiteral(_) => self.advance().text.clone(),
+                _ => self.parse_identifier_name()?,
+            };
+            let alias = if self.is_word("as") {
+                self.advance();
+                Some(self.parse_identifier_name()?)
+            } else {
+                None
+            };
+            specifiers.push(ModuleSpecifier {
+                name,
+                alias,
+                span: self.span_from(start),
+            });
+
+            if !self.at(&TokenKind::CloseBrace) {
+                self.expect(&TokenKind::Comma)?;
+            }
+        }
+
+        Ok(specifiers)
+    }
+
+    fn parse_module_source(&mut self) -> Result<String> {
+        match self.current().kind {
+            TokenKind::StringLiteral(_) => Ok(self.advance().text.clone()),
+            _ => Err(self.unexpected()),
+        }
+    }
+
+    // Functions and classes
+
+    /// Parse a function starting at the `function` keyword
+    fn parse_function(&mut self, is_async: bool) -> Result<Function> {
+        self.expect_keyword("function")?;
+        let is_generator = self.eat_op("*");
+        let name = if self.is_identifier() {
+            Some(self.parse_identifier()?)
+        } else {
+            None
+        };
+        let (params, body) = self.in_function(is_async, is_generator, |parser| {
+            Ok((parser.parse_params()?, parser.parse_function_body()?))
+        })?;
+
+        Ok(Function {
+            name,
+            is_async,
+            is_generator,
+            params,
+            body,
+        })
+    }
+
+    /// Parse the parameters and body of a method
+    fn parse_method(&mut self, is_async: bool, is_generator: bool) -> Result<Function> {
+        let (params, body) = self.in_function(is_async, is_generator, |parser| {
+            Ok((parser.parse_params()?, parser.parse_function_body()?))
+        })?;
+
+        Ok(Function {
+            name: None,
+            is_async,
+            is_generator,
+            params,
+            body,
+        })
+    }
+
+    /// Parse a parenthesized parameter list
+    fn parse_params(&mut self) -> Result<Vec<Expression>> {
+        self.expect(&TokenKind::OpenParen)?;
+        self.allow_in(|parser| {
+            let mut params = Vec::new();
+            while !parser.eat(&TokenKind::CloseParen) {
+                params.push(parser.parse_spread_or_assignment()?);
+                if !parser.at(&TokenKind::CloseParen) {
+                    parser.expect(&TokenKind::Comma)?;
+                }
+            }
+            Ok(params)
+        })
+    }
+
+    fn parse_function_body(&mut self) -> Result<Block> {
+        self.allow_in(Self::parse_block)
+    }
+
+    fn parse_class(&mut self) -> Result<Class> {
+        let start = self.current().span;
+        self.expect_keyword("class")?;
+        let name = if self.is_identifier() {
+            Some(self.parse_identifier()?)
+        } else {
+            None
+        };
+        let super_class = if self.is_keyword("extends") {
+            self.advance();
+            Some(Box::new(self.parse_call_or_member()?))
+        } else {
+            None
+        };
+
+        self.expect(&TokenKind::OpenBrace)?;
+        let body = self.allow_in(|parser| {
+            let mut body = Vec::new();
+            while !parser.eat(&TokenKind::CloseBrace) {
+                body.push(parser.parse_class_member()?);
+            }
+            Ok(body)
+        })?;
+
+        Ok(Class {
+            name,
+            super_class,
+            body,
+            span: self.span_from(start),
+        })
+    }
+
+    /// Check if the token at `index` ends a property name, so a preceding
+    /// `get`, `set`, `static` or `async` is the name itself rather than a modifier
+    fn is_property_name_end(&self, index: usize) -> bool {
+        matches!(
+            self.token_at(index).kind,
+            TokenKind::Comma
+                | TokenKind::Colon
+                | TokenKind::Semicolon
+                | TokenKind::OpenParen
+                | TokenKind::CloseBrace
+                | TokenKind::EndOfFile
+        ) || self.is_op_at(index, "=")
+    }
+
+    fn parse_class_member(&mut self) -> Result<ClassMember> {
+        let start = self.current().span;
+
+        if self.eat(&TokenKind::Semicolon) {
+            return Ok(ClassMember {
+                kind: ClassMemberKind::Empty,
+                span: self.span_from(start),
+            });
+        }
+
+        let is_static = self.is_word("static") && !self.is_property_name_end(self.pos + 1);
+        if is_static {
+            self.advance();
+            if self.at(&TokenKind::OpenBrace) {
+                let block = self.parse_block()?;
+                return Ok(ClassMember {
+                    kind: ClassMemberKind::StaticBlock(block),
+                    span: self.span_from(start),
+                });
+            }
+        }
+
+        let (is_async, is_generator, kind) = self.parse_method_modifiers();
+        let key = self.parse_property_key()?;
+
+        let kind = if self.at(&TokenKind::OpenParen) {
+            let kind = match &key {
+                PropertyKey::Identifier(name)
+                    if name == "constructor" && kind == MethodKind::Method && !is_static =>
+                {
+                    MethodKind::Constructor
+                }
+                _ => kind,
+            };
+            ClassMemberKind::Method {
+                is_static,
+                kind,
+                key,
+                function: self.parse_method(is_async, is_generator)?,
+            }
+        } else if is_async || is_generator || kind != MethodKind::Method {
+            return Err(self.unexpected());
+        } else {
+            let value = if self.eat_op("=") {
+                Some(self.parse_assignment()?)
+            } else {
+                None
+            };
+            self.consume_semicolon()?;
+            ClassMemberKind::Field {
+                is_static,
+                key,
+                value,
+            }
+        };
+
+        Ok(ClassMember {
+            kind,
+            span: self.span_from(start),
+        })
+    }
+
+    /// Parse the `async`, `*`, `get` and `set` modifiers in front of a method name
+    fn parse_method_modifiers(&mut self) -> (bool, bool, MethodKind) {
+        let is_async = self.is_word("async")
+            && !self.is_property_name_end(self.pos + 1)
+            && !self.newline_before_at(self.pos + 1);
+        if is_async {
+            self.advance();
+        }
+
+        let is_generator = self.eat_op("*");
+
+        let mut kind = MethodKind::Method;
+        if !is_async && !is_generator && !self.is_property_name_end(self.pos + 1) {
+            if self.is_word("get") {
+                kind = MethodKind::Getter;
+            } else if self.is_word("set") {
+                kind = MethodKind::Setter;
+            }
+            if kind != MethodKind::Method {
+                self.advance();
+            }
+        }
+
+        (is_async, is_generator, kind)
+    }
+
+    fn parse_property_key(&mut self) -> Result<PropertyKey> {
+        match self.current().kind {
+            TokenKind::Identifier(_) | TokenKind::Keyword(_) => {
+                Ok(PropertyKey::Identifier(self.advance().text.clone()))
+            }
+            TokenKind::StringLiteral(_) => Ok(PropertyKey::String(self.advance().text.clone())),
+            TokenKind::NumberLiteral(_) => Ok(PropertyKey::Number(self.advance().text.clone())),
+            TokenKind::PrivateName(_) => Ok(PropertyKey::Private(self.advance().text.clone())),
+            TokenKind::OpenBracket => {
+                self.advance();
+                let key = self.allow_in(Self::parse_assignment)?;
+                self.expect(&TokenKind::CloseBracket)?;
+                Ok(PropertyKey::Computed(Box::new(key)))
+            }
+            _ => Err(self.unexpected()),
+        }
+    }
+
+    // Expressions
+
+    fn parse_expression(&mut self) -> Result<Expression> {
+        let start = self.current().span;
+        let first = self.parse_assignment()?;
+        if !self.at(&TokenKind::Comma) {
+            return Ok(first);
+        }
+
+        let mut expressions = vec![first];
+        while self.eat(&TokenKind::Comma) {
+            expressions.push(self.parse_assignment()?);
+        }
+        Ok(Expression::new(
+            ExpressionKind::Sequence(expressions),
+            self.span_from(start),
+        ))
+    }
+
+    /// Parse an assignment expression or a spread element
+    fn parse_spread_or_assignment(&mut self) -> Result<Expression> {
+        let start = self.current().span;
+        if self.eat(&TokenKind::Ellipsis) {
+            let argument = self.parse_assignment()?;
+            return Ok(Expression::new(
+                ExpressionKind::Spread(Box::new(argument)),
+                self.span_from(start),
+            ));
+        }
+        self.parse_assignment()
+    }
+
+    fn parse_assignment(&mut self) -> Result<Expression> {
+        self.nested(Self::parse_assignment_inner)
+    }
+
+    fn parse_assignment_inner(&mut self) -> Result<Expression> {
+        let start = self.current().span;
+
+        if self.is_word("async")
+            && !self.newline_before_at(self.pos + 1)
+            && self.is_arrow_at(self.pos + 1)
+        {
+            self.advance();
+            return self.parse_arrow(true, start);
+        }
+        if self.is_arrow_at(self.pos) {
+            return self.parse_arrow(false, start);
+        }
+        if self.in_generator && self.is_keyword("yield") {
+            return self.parse_yield();
+        }
+
+        let left = self.parse_conditional()?;
+        let operator = match &self.current().kind {
+            TokenKind::Operator(op) if ASSIGNMENT_OPERATORS.contains(&op.as_str()) => op.clone(),
+            _ => return Ok(left),
+        };
+        self.advance();
+        let right = self.parse_assignment()?;
+
+        Ok(Expression::new(
+            ExpressionKind::Assignment {
+                operator,
+                left: Box::new(left),
+                right: Box::new(right),
+            },
+            self.span_from(start),
+        ))
+    }
+
+    fn parse_arrow(&mut self, is_async: bool, start: Span) -> Result<Expression> {
+        self.in_function(is_async, false, |parser| {
+            parser.parse_arrow_function(is_async, start)
+        })
+    }
+
+    fn parse_arrow_function(&mut self, is_async: bool, start: Span) -> Result<Expression> {
+        let (params, parenthesized) = if self.at(&TokenKind::OpenParen) {
+            (self.parse_params()?, true)
+        } else {
+            let param = self.parse_binding_target()?;
+            (vec![param], false)
+        };
+        let params_end = self.span_from(start).end;
+        self.expect_op("=>")?;
+
+        let body = if self.at(&TokenKind::OpenBrace) {
+            ArrowBody::Block(self.parse_function_body()?)
+        } else {
+            ArrowBody::Expression(Box::new(self.parse_assignment()?))
+        };
+
+        Ok(Expression::new(
+            ExpressionKind::ArrowFunction(Box::new(ArrowFunction {
+                is_async,
+                params,
+                parenthesized,
+                params_end,
+                body,
+            })),
+            self.span_from(start),
+        ))
+    }
+
+    fn parse_yield(&mut self) -> Result<Expression> {
+        let start = self.current().span;
+        self.advance();
+
+        let delegate = !self.newline_before() && self.eat_op("*");
+        let has_argument = delegate
+            || !(self.newline_before()
+                || matches!(
+                    &self.current().kind,
+                    TokenKind::CloseParen
+                        | TokenKind::CloseBracket
+                        | TokenKind::CloseBrace
+                        | TokenKind::Comma
+                        | TokenKind::Semicolon
+                        | TokenKind::Colon
+                        | TokenKind::EndOfFile
+                )
+                || matches!(&self.current().kind, TokenKind::Operator(op) if !PREFIX_OPERATORS.contains(&op.as_str())));
+        let argument = if has_argument {
+            Some(Box::new(self.parse_assignment()?))
+        } else {
+            None
+        };
+
+        Ok(Expression::new(
+            ExpressionKind::Yield { argument, delegate },
+            self.span_from(start),
+        ))
+    }
+
+    fn parse_conditional(&mut self) -> Result<Expression> {
+        let start = self.current().span;
+        let test = self.parse_binary(0)?;
+        if !self.eat_op("?") {
+            return Ok(test);
+        }
+
+        let consequent = self.allow_in(Self::parse_assignment)?;
+        self.expect(&TokenKind::Colon)?;
+        let alternate = self.parse_assignment()?;
+
+        Ok(Expression::new(
+            ExpressionKind::Conditional {
+                test: Box::new(test),
+                consequent: Box::new(consequent),
+                alternate: Box::new(alternate),
+            },
+            self.span_from(start),
+        ))
+    }
+
+    /// The binary operator at the current token and its precedence
+    fn binary_operator(&self) -> Option<(&'a str, u8)> {
+        let operator = match &self.current().kind {
+            TokenKind::Operator(op) => op.as_str(),
+            TokenKind::Keyword(keyword) if keyword == "instanceof" => keyword.as_str(),
+            TokenKind::Keyword(keyword) if keyword == "in" && !self.no_in => keyword.as_str(),
+            _ => return None,
+        };
+        binary_precedence(operator).map(|precedence| (operator, precedence))
+    }
+
+    /// Parse binary operators binding tighter than `min_precedence`
+    fn parse_binary(&mut self, min_precedence: u8) -> Result<Expression> {
+        self.chain(|parser| parser.parse_binary_chain(min_precedence))
+    }
+
+    fn parse_binary_chain(&mut self, min_precedence: u8) -> Result<Expression> {
+        let start = self.current().span;
+        let mut left = self.parse_unary()?;
+
+        while let Some((operator, precedence)) = self.binary_operator() {
+            if precedence <= min_precedence {
+                break;
+            }
+            // `-a ** b` is ambiguous, so the left side of `**` cannot be a unary
+            // expression without parentheses
+            let unary = matches!(
+                left.kind,
+                ExpressionKind::Unary { .. } | ExpressionKind::Await(_)
+            );
+            if operator == "**" && unary {
+                return Err(self.unexpected());
+            }
+            self.deepen()?;
+            self.advance();
+
+            // Exponentiation is right-associative
+            let right = if operator == "**" {
+                self.parse_binary(precedence - 1)?
+            } else {
+                self.parse_binary(precedence)?
+            };
+            left = Expression::new(
+                ExpressionKind::Binary {
+                    operator: operator.to_string(),
+                    left: Box::new(left),
+                    right: Box::new(right),
+                },
+                self.span_from(start),
+            );
+        }
+
+        Ok(left)
+    }
+
+    fn parse_unary(&mut self) -> Result<Expression> {
+        let start = self.current().span;
+
+        match &self.current().kind {
+            TokenKind::Operator(operator) if PREFIX_OPERATORS.contains(&operator.as_str()) => {
+                self.advance();
+                let argument = Box::new(self.nested(Self::parse_unary)?);
+                let kind = if operator == "++" || operator == "--" {
+                    ExpressionKind::Update {
+                        operator: operator.clone(),
+                        prefix: true,
+                        argument,
+                    }
+                } else {
+                    ExpressionKind::Unary {
+                        operator: operator.clone(),
+                        argument,
+                    }
+                };
+                Ok(Expression::new(kind, self.span_from(start)))
+            }
+            TokenKind::Keyword(keyword)
+                if matches!(keyword.as_str(), "typeof" | "void" | "delete") =>
+            {
+                self.advance();
+                let argument = self.nested(Self::parse_unary)?;
+                Ok(Expression::new(
+                    ExpressionKind::Unary {
+                        operator: keyword.clone(),
+                        argument: Box::new(argument),
+                    },
+                    self.span_from(start),
+                ))
+            }
+            TokenKind::Keyword(keyword) if keyword == "await" && self.in_async => {
+                self.advance();
+                let argument = self.nested(Self::parse_unary)?;
+                Ok(Expression::new(
+                    ExpressionKind::Await(Box::new(argument)),
+                    self.span_from(start),
+                ))
+            }
+            _ => self.parse_postfix(),
+        }
+    }
+
+    fn parse_postfix(&mut self) -> Result<Expression> {
+        let start = self.current().span;
+        let expression = self.parse_call_or_member()?;
+
+        if (self.is_op("++") || self.is_op("--")) && !self.newline_before() {
+            let operator = self.advance().text.clone();
+            return Ok(Expression::new(
+                ExpressionKind::Update {
+                    operator,
+                    prefix: false,
+                    argument: Box::new(expression),
+                },
+                self.span_from(start),
+            ));
+        }
+
+        Ok(expression)
+    }
+
+    /// Parse a primary expression followed by calls and member accesses
+    fn parse_call_or_member(&mut self) -> Result<Expression> {
+        self.chain(Self::parse_call_or_member_chain)
+    }
+
+    fn parse_call_or_member_chain(&mut self) -> Result<Expression> {
+        let start = self.current().span;
+        let mut expression = if self.is_keyword("new") {
+            self.parse_new()?
+        } else {
+            self.parse_primary()?
+        };
+
+        loop {
+            if matches!(
+                self.current().kind,
+                TokenKind::OpenParen
+                    | TokenKind::Dot
+                    | TokenKind::OpenBracket
+                    | TokenKind::QuestionDot
+                    | TokenKind::Template(TemplateKind::NoSubstitution | TemplateKind::Head, _)
+            ) {
+                self.deepen()?;
+            }
+            expression = match self.current().kind {
+                TokenKind::OpenParen => self.parse_call(expression, start, false)?,
+                TokenKind::Dot | TokenKind::OpenBracket => {
+                    self.parse_member(expression, start, false)?
+                }
+                TokenKind::QuestionDot => {
+                    self.advance();
+                    match self.current().kind {
+                        TokenKind::OpenParen => self.parse_call(expression, start, true)?,
+                        _ => self.parse_member(expression, start, true)?,
+                    }
+                }
+                TokenKind::Template(TemplateKind::NoSubstitution | TemplateKind::Head, _) => {
+                    let quasi = self.parse_template()?;
+                    Expression::new(
+                        ExpressionKind::TaggedTemplate {
+                            tag: Box::new(expression),
+                            quasi: Box::new(quasi),
+                        },
+                        self.span_from(start),
+                    )
+                }
+                _ => return Ok(expression),
+            };
+        }
+    }
+
+    /// Parse the arguments of a call of `callee`, which follow `?.` if `optional`
+    fn parse_call(
+        &mut self,
+        callee: Expression,
+        start: Span,
+        optional: bool,
+    ) -> Result<Expression> {
+        let arguments = self.parse_arguments()?;
+        Ok(Expression::new(
+            ExpressionKind::Call {
+                callee: Box::new(callee),
+                arguments,
+                optional,
+            },
+            self.span_from(start),
+        ))
+    }
+
+    /// Parse a `.name`, `.#name` or `[expression]` member access on `object`. After
+    /// `?.` (`optional`) the name follows without a dot.
+    fn parse_member(
+        &mut self,
+        object: Expression,
+        start: Span,
+        optional: bool,
+    ) -> Result<Expression> {
+        let computed = self.at(&TokenKind::OpenBracket);
+        if computed || !optional {
+            self.advance();
+        }
+
+        let property = if computed {
+            let property = self.allow_in(Self::parse_expression)?;
+            self.expect(&TokenKind::CloseBracket)?;
+            property
+        } else {
+            let name_start = self.current().span;
+            let name = match self.current().kind {
+                TokenKind::PrivateName(_) => self.advance().text.clone(),
+                _ => self.parse_identifier_name()?,
+            };
+            Expression::new(ExpressionKind::Identifier(name), self.span_from(name_start))
+        };
+
+        Ok(Expression::new(
+            ExpressionKind::Member {
+                object: Box::new(object),
+                property: Box::new(property),
+                computed,
+                optional,
+            },
+            self.span_from(start),
+        ))
+    }
+
+    fn parse_new(&mut self) -> Result<Expression> {
+        let start = self.current().span;
+        self.advance();
+
+        if self.eat(&TokenKind::Dot) {
+            let property = self.parse_identifier_name()?;
+            return Ok(Expression::new(
+                ExpressionKind::MetaProperty {
+                    meta: "new".to_string(),
+                    property,
+                },
+                self.span_from(start),
+            ));
+        }
+
+        let callee_start = self.current().span;
+        let mut callee = if self.is_keyword("new") {
+            self.nested(Self::parse_new)?
+        } else {
+            self.parse_primary()?
+        };
+        while matches!(self.current().kind, TokenKind::Dot | TokenKind::OpenBracket) {
+            self.deepen()?;
+            callee = self.parse_member(callee, callee_start, false)?;
+        }
+
+        let arguments = if self.at(&TokenKind::OpenParen) {
+            Some(self.parse_arguments()?)
+        } else {
+            None
+        };
+
+        Ok(Expression::new(
+            ExpressionKind::New {
+                callee: Box::new(callee),
+                arguments,
+            },
+            self.span_from(start),
+        ))
+    }
+
+    fn parse_arguments(&mut self) -> Result<Vec<Expression>> {
+        self.expect(&TokenKind::OpenParen)?;
+        self.allow_in(|parser| {
+            let mut arguments = Vec::new();
+            while !parser.eat(&TokenKind::CloseParen) {
+                arguments.push(parser.parse_spread_or_assignment()?);
+                if !parser.at(&TokenKind::CloseParen) {
+                    parser.expect(&TokenKind::Comma)?;
+                }
+            }
+            Ok(arguments)
+        })
+    }
+
+    fn parse_primary(&mut self) -> Result<Expression> {
+        let start = self.current().span;
+        let token = self.current();
+
+        let kind = match &token.kind {
+            TokenKind::NumberLiteral(_) | TokenKind::RegexLiteral(_) => {
+                self.advance();
+                ExpressionKind::Literal(token.text.clone())
+            }
+            TokenKind::StringLiteral(_) => {
+                self.advance();
+                ExpressionKind::String(token.text.clone())
+            }
+            TokenKind::Identifier(name) => {
+                self.advance();
+                ExpressionKind::Identifier(name.clone())
+            }
+            // The left side of an `#name in object` check
+            TokenKind::PrivateName(name) if self.is_keyword_at(self.pos + 1, "in") => {
+                self.advance();
+                ExpressionKind::Identifier(name.clone())
+            }
+            TokenKind::OpenParen => {
+                self.advance();
+                let expression = self.allow_in(Self::parse_expression)?;
+                self.expect(&TokenKind::CloseParen)?;
+                ExpressionKind::Parenthesized(Box::new(expression))
+            }
+            TokenKind::Template(TemplateKind::NoSubstitution | TemplateKind::Head, _) => {
+                return self.parse_template();
+            }
+            TokenKind::OpenBracket => return self.parse_array(),
+            TokenKind::OpenBrace => return self.parse_object(),
+            TokenKind::Keyword(keyword) => match keyword.as_str() {
+                "this" => {
+                    self.advance();
+                    ExpressionKind::This
+                }
+                "super" => {
+                    self.advance();
+                    ExpressionKind::Super
+                }
+                "true" | "false" | "null" => {
+                    self.advance();
+                    ExpressionKind::Literal(keyword.clone())
+                }
+                "function" => ExpressionKind::Function(Box::new(self.parse_function(false)?)),
+                "async" if self.is_async_function() => {
+                    self.advance();
+                    ExpressionKind::Function(Box::new(self.parse_function(true)?))
+                }
+                "class" => ExpressionKind::Class(Box::new(self.parse_class()?)),
+                "import" if self.at_index(self.pos + 1, &TokenKind::Dot) => {
+                    self.advance();
+                    self.advance();
+                    ExpressionKind::MetaProperty {
+                        meta: "import".to_string(),
+                        property: self.parse_identifier_name()?,
+                    }
+                }
+                // Dynamic `import(...)`, the call itself is parsed by the caller
+                "import" if self.at_index(self.pos + 1, &TokenKind::OpenParen) => {
+                    self.advance();
+                    ExpressionKind::Identifier(keyword.clone())
+                }
+                _ if self.is_identifier() => {
+                    self.advance();
+                    ExpressionKind::Identifier(keyword.clone())
+                }
+                _ => return Err(self.unexpected()),
+            },
+            _ => return Err(self.unexpected()),
+        };
+
+        Ok(Expression::new(kind, self.span_from(start)))
+    }
+
+    /// Parse a template literal and the expressions of its substitutions
+    fn parse_template(&mut self) -> Result<Expression> {
+        let start = self.current().span;
+        let mut quasis = Vec::new();
+        let mut expressions = Vec::new();
+
+        loop {
+            let token = self.current();
+            let TokenKind::Template(kind, _) = token.kind else {
+                return Err(self.unexpected());
+            };
+            match (quasis.is_empty(), kind) {
+                (true, TemplateKind::NoSubstitution) | (false, TemplateKind::Tail) => {
+                    self.advance();
+                    quasis.push(token.text.clone());
+                    break;
+                }
+                (true, TemplateKind::Head) | (false, TemplateKind::Middle) => {
+                    self.advance();
+                    quasis.push(token.text.clone());
+                    expressions.push(self.allow_in(Self::parse_expression)?);
+                }
+                _ => return Err(self.unexpected()),
+            }
+        }
+
+        Ok(Expression::new(
+            ExpressionKind::Template {
+                quasis,
+                expressions,
+            },
+            self.span_from(start),
+        ))
+    }
+
+    fn parse_array(&mut self) -> Result<Expression> {
+        let start = self.current().span;
+        self.expect(&TokenKind::OpenBracket)?;
+
+        let elements = self.allow_in(|parser| {
+            let mut elements = Vec::new();
+            while !parser.eat(&TokenKind::CloseBracket) {
+                if parser.eat(&TokenKind::Comma) {
+                    elements.push(None);
+                    continue;
+                }
+                elements.push(Some(parser.parse_spread_or_assignment()?));
+                if !parser.at(&TokenKind::CloseBracket) {
+                    parser.expect(&TokenKind::Comma)?;
+                }
+            }
+            Ok(elements)
+        })?;
+
+        Ok(Expression::new(
+            ExpressionKind::Array(elements),
+            self.span_from(start),
+        ))
+    }
+
+    fn parse_object(&mut self) -> Result<Expression> {
+        let start = self.current().span;
+        self.expect(&TokenKind::OpenBrace)?;
+
+        let properties = self.allow_in(|parser| {
+            let mut properties = Vec::new();
+            while !parser.eat(&TokenKind::CloseBrace) {
+                properties.push(parser.parse_property()?);
+                if !parser.at(&TokenKind::CloseBrace) {
+                    parser.expect(&TokenKind::Comma)?;
+                }
+            }
+            Ok(properties)
+        })?;
+
+        Ok(Expression::new(
+            ExpressionKind::Object(properties),
+            self.span_from(start),
+        ))
+    }
+
+    fn parse_property(&mut self) -> Result<Property> {
+        let start = self.current().span;
+
+        if self.eat(&TokenKind::Ellipsis) {
+            let argument = self.parse_assignment()?;
+            return Ok(Property {
+                kind: PropertyKind::Spread(argument),
+                span: self.span_from(start),
+            });
+        }
+
+        let (is_async, is_generator, method_kind) = self.parse_method_modifiers();
+        let key_start = self.current().span;
+        let key = self.parse_property_key()?;
+
+        let kind = if self.at(&TokenKind::OpenParen) {
+            PropertyKind::Method {
+                kind: method_kind,
+                key,
+                function: self.parse_method(is_async, is_generator)?,
+            }
+        } else if is_async || is_generator || method_kind != MethodKind::Method {
+            return Err(self.unexpected());
+        } else if self.eat(&TokenKind::Colon) {
+            PropertyKind::KeyValue {
+                key,
+                value: self.parse_assignment()?,
+            }
+        } else if let PropertyKey::Identifier(name) = key {
+            let mut value =
+                Expression::new(ExpressionKind::Identifier(name), self.span_from(key_start));
+            // A default value in a destructuring pattern, such as `{ a = 1 }`
+            if self.eat_op("=") {
+                let default = self.parse_assignment()?;
+                value = Expression::new(
+                    ExpressionKind::Assignment {
+                        operator: "=".to_string(),
+                        left: Box::new(value),
+                        right: Box::new(default),
+                    },
+                    self.span_from(key_start),
+                );
+            }
+            PropertyKind::Shorthand(value)
+        } else {
+            return Err(self.unexpected());
+        };
+
+        Ok(Property {
+            kind,
+            span: self.span_from(start),
+        })
+    }
+}
@@ -0,0 +1,1889 @@
+//! Printer turning a JavaScript syntax tree into a document
+
+use super::ast::*;
+use crate::core::doc::Doc;
+use crate::core::options::{BraceStyle, FormatOptions, TrailingCommas};
+use crate::core::tokens::{
+    is_line_terminator, line_break_count, CommentKind, Span, Token, TokenKind,
+};
+
+/// Print a program with the given options
+///
+/// `tokens` is the lossless token stream the program was parsed from, and provides
+/// the comments to print.
+pub(crate) fn print_program(
+    program: &Program,
+    content: &str,
+    tokens: &[Token],
+    options: &FormatOptions,
+) -> String {
+    let mut printer = Printer::new(content, tokens, options);
+    let doc = printer.print_lines(
+        &statement_list(&program.body),
+        |statement| statement.span,
+        |printer, statement, _| printer.print_statement(statement),
+        usize::MAX,
+    );
+
+    let mut output = doc.print(options);
+    if !output.is_empty() {
+        output.push('\n');
+    }
+    output
+}
+
+/// Check if a binary expression nested on the left of `parent` can be printed as
+/// part of one chain, as in `a + b + c`
+fn should_flatten(parent: &str, child: &str) -> bool {
+    binary_precedence(parent) == binary_precedence(child) && parent != "**"
+}
+
+/// Check if a call argument can be expanded in place, such as a callback or an object
+fn is_huggable(expression: &Expression) -> bool {
+    match &expression.kind {
+        ExpressionKind::Function(_) | ExpressionKind::Class(_) => true,
+        ExpressionKind::ArrowFunction(arrow) => matches!(arrow.body, ArrowBody::Block(_)),
+        ExpressionKind::Object(properties) => !properties.is_empty(),
+        _ => false,
+    }
+}
+
+/// Check if a call argument is short enough to follow a hugged callback
+fn is_simple(expression: &Expression) -> bool {
+    matches!(
+        expression.kind,
+        ExpressionKind::Identifier(_)
+            | ExpressionKind::Literal(_)
+            | ExpressionKind::String(_)
+            | ExpressionKind::This
+    )
+}
+
+/// Check if an expression is a number, possibly negated
+fn is_number(expression: &Expression) -> bool {
+    match &expression.kind {
+        ExpressionKind::Literal(raw) => raw.starts_with(|c: char| c.is_ascii_digit() || c == '.'),
+        ExpressionKind::Unary { operator, argument } => operator == "-" && is_number(argument),
+        _ => false,
+    }
+}
+
+/// Check if a parameter is a destructuring pattern, which hugs the parentheses
+fn is_pattern(expression: &Expression) -> bool {
+    match &expression.kind {
+        ExpressionKind::Object(_) | ExpressionKind::Array(_) => true,
+        ExpressionKind::Assignment { left, .. } => is_pattern(left),
+        _ => false,
+    }
+}
+
+/// The statements of a list worth printing: empty statements are dropped
+fn statement_list(statements: &[Statement]) -> Vec<&Statement> {
+    statements
+        .iter()
+        .filter(|statement| !matches!(statement.kind, StatementKind::Empty))
+        .collect()
+}
+
+/// Check if an expression statement would continue the previous line without a
+/// semicolon before it, because it starts with `(`, `[`, a template, `+`, `-` or a
+/// regular expression
+fn starts_with_asi_hazard(expression: &Expression) -> bool {
+    match &expression.kind {
+        ExpressionKind::Parenthesized(_)
+        | ExpressionKind::Array(_)
+        | ExpressionKind::Template { .. } => true,
+        ExpressionKind::Literal(raw) => raw.starts_with('/'),
+        ExpressionKind::Unary { operator, .. } => operator == "+" || operator == "-",
+        ExpressionKind::Update {
+            operator,
+            prefix,
+            argument,
+        } => {
+            if *prefix {
+                operator == "++" || operator == "--"
+            } else {
+                starts_with_asi_hazard(argument)
+            }
+        }
+        ExpressionKind::ArrowFunction(arrow) => !arrow.is_async && arrow.parenthesized,
+        ExpressionKind::Binary { left, .. } | ExpressionKind::Assignment { left, .. } => {
+            starts_with_asi_hazard(left)
+        }
+        ExpressionKind::Conditional { test, .. } => starts_with_asi_hazard(test),
+        ExpressionKind::Call { callee, .. } => starts_with_asi_hazard(callee),
+        ExpressionKind::Member { object, .. } => starts_with_asi_hazard(object),
+        ExpressionKind::TaggedTemplate { tag, .. } => starts_with_asi_hazard(tag),
+        ExpressionKind::Sequence(expressions) => {
+            expressions.first().is_some_and(starts_with_asi_hazard)
+        }
+        _ => false,
+    }
+}
+
+/// Check if a class field needs its `;` even when semicolons are omitted, because
+/// the next member would otherwise continue it, as in `a` followed by `[b] = 1` or
+/// `*gen() {}`, or because a field named `get`, `set` or `static` would modify the
+/// next member. These are the cases Prettier guards against.
+fn field_needs_semicolon(field: &ClassMember, next: Option<&ClassMember>) -> bool {
+    if let ClassMemberKind::Field {
+        is_static,
+        key: PropertyKey::Identifier(name),
+        value: None,
+    } = &field.kind
+    {
+        if !is_static && matches!(name.as_str(), "get" | "set" | "static") {
+            return true;
+        }
+    }
+
+    match next.map(|next| &next.kind) {
+        Some(ClassMemberKind::Field {
+            is_static: false,
+            key,
+            ..
+        }) => match key {
+            PropertyKey::Computed(_) => true,
+            PropertyKey::Identifier(name) => name == "in" || name == "instanceof",
+            _ => false,
+        },
+        Some(ClassMemberKind::Method {
+            is_static: false,
+            kind,
+            key,
+            function,
+        }) => {
+            let modified =
+                function.is_async || matches!(kind, MethodKind::Getter | MethodKind::Setter);
+            let starts_with_name = matches!(key, PropertyKey::Identifier(name) if name == "in" || name == "instanceof");
+            !modified
+                && (function.is_generator
+                    || matches!(key, PropertyKey::Computed(_))
+                    || starts_with_name)
+        }
+        _ => false,
+    }
+}
+
+fn is_spread(expression: Option<&Expression>) -> bool {
+    expression.is_some_and(|expression| matches!(expression.kind, ExpressionKind::Spread(_)))
+}
+
+/// Turn the `\r\n` and `\r` line breaks of source `text` into the `\n` the printer
+/// emits, which the formatter converts to `end_of_line`
+///
+/// JavaScript normalizes the line breaks of template literals the same way, so their
+/// values do not change.
+fn normalize_line_breaks(text: &str) -> String {
+    text.replace("\r\n", "\n").replace('\r', "\n")
+}
+
+/// Print a comment with its delimiters
+///
+/// The `*` lines of a JSDoc-style block comment are re-indented to line up under the
+/// opening `/*` at the comment's new indentation. Other block comments are kept as
+/// written.
+fn print_comment(comment: &Token) -> Doc {
+    let text = normalize_line_breaks(&comment.text);
+    let mut lines = text.split(is_line_terminator);
+    let first = lines.next().unwrap_or_default();
+    let rest: Vec<&str> = lines.map(str::trim).collect();
+    if rest.is_empty() {
+        return Doc::text(first);
+    }
+    if !rest.iter().all(|line| line.starts_with('*')) {
+        return Doc::text(text.replace(is_line_terminator, "\n"));
+    }
+
+    let mut parts = vec![Doc::text(first.trim_end())];
+    for line in rest {
+        parts.push(Doc::hardline());
+        parts.push(Doc::text(format!(" {}", line)));
+    }
+    Doc::concat(parts)
+}
+
+/// The quote to wrap string `contents` in: `preferred`, unless the contents hold more
+/// of it than of the other quote
+fn choose_quote(contents: &str, preferred: char) -> char {
+    let alternate = if preferred == '"' { '\'' } else { '"' };
+    if contents.matches(preferred).count() > contents.matches(alternate).count() {
+        alternate
+    } else {
+        preferred
+    }
+}
+
+/// Wrap the raw `contents` of a string literal in `quote`, escaping that quote and
+/// dropping escapes the other quote no longer needs. Other escapes are kept as
+/// written.
+fn quote_string(contents: &str, quote: char) -> String {
+    let mut string = String::with_capacity(contents.len() + 2);
+    string.push(quote);
+
+    let mut chars = contents.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => match chars.next() {
+                Some(escaped @ ('"' | '\'')) if escaped != quote => string.push(escaped),
+                Some(escaped) => {
+                    string.push('\\');
+                    string.push(escaped);
+                }
+                None => string.push('\\'),
+            },
+            _ if c == quote => {
+                string.push('\\');
+                string.push(c);
+            }
+            _ => string.push(c),
+        }
+    }
+
+    string.push(quote);
+    string
+}
+
+/// Rewrite a numeric literal in canonical form: lowercase prefixes and exponents,
+/// uppercase hex digits and no trailing zeros after the decimal point
+fn normalize_number(raw: &str) -> String {
+    let number = raw.to_ascii_lowercase();
+    if let Some(digits) = number.strip_prefix("0x") {
+        let (digits, bigint) = match digits.strip_suffix('n') {
+            Some(digits) => (digits, "n"),
+            None => (digits, ""),
+        };
+        return format!("0x{}{}", digits.to_ascii_uppercase(), bigint);
+    }
+    if number.starts_with("0b") || number.starts_with("0o") || !number.contains('.') {
+        return number;
+    }
+
+    let (mantissa, exponent) = number.split_at(number.find('e').unwrap_or(number.len()));
+    let mantissa = mantissa.trim_end_matches(['0', '_']).trim_end_matches('.');
+    let mantissa = if mantissa.is_empty() { "0" } else { mantissa };
+    format!("{}{}", mantissa, exponent)
+}
+
+/// Add `doc` to `lines` on a line of its own, after an empty line if `blank_line`
+fn push_line(lines: &mut Vec<Doc>, doc: Doc, blank_line: bool) {
+    if !lines.is_empty() {
+        lines.push(Doc::hardline());
+        if blank_line {
+            lines.push(Doc::hardline());
+        }
+    }
+    lines.push(doc);
+}
+
+/// A comma-separated list that breaks into one item per line if it does not fit
+fn list(open: &str, close: &str, items: Vec<Doc>, trailing_comma: bool) -> Doc {
+    if items.is_empty() {
+        return Doc::text(format!("{}{}", open, close));
+    }
+
+    let trailing_comma = if trailing_comma {
+        Doc::if_break(Doc::text(","), Doc::nil())
+    } else {
+        Doc::nil()
+    };
+    Doc::group(Doc::concat(vec![
+        Doc::text(open),
+        Doc::indent(Doc::concat(vec![
+            Doc::softline(),
+            Doc::join(Doc::concat(vec![Doc::text(","), Doc::line()]), items),
+            trailing_comma,
+        ])),
+        Doc::softline(),
+        Doc::text(close),
+    ]))
+}
+
+struct Printer<'a> {
+    content: &'a str,
+    options: &'a FormatOptions,
+    /// All comments of the source, in order
+    comments: Vec<&'a Token>,
+    printed: Vec<bool>,
+    /// Index of the first comment that has not been printed
+    next_comment: usize,
+    /// End offset of the last node or comment that ended a line
+    last_end: usize,
+}
+
+impl<'a> Printer<'a> {
+    fn new(content: &'a str, tokens: &'a [Token], options: &'a FormatOptions) -> Self {
+        let comments: Vec<&Token> = tokens
+            .iter()
+            .flat_map(|token| token.leading_trivia.iter().chain(&token.trailing_trivia))
+            .filter(|trivia| matches!(trivia.kind, TokenKind::Comment(..)))
+            .collect();
+
+        Self {
+            content,
+            options,
+            printed: vec![false; comments.len()],
+            comments,
+            next_comment: 0,
+            last_end: 0,
+        }
+    }
+
+    fn semicolon(&self) -> Doc {
+        if self.options.semicolons {
+            Doc::text(";")
+        } else {
+            Doc::nil()
+        }
+    }
+
+    /// Separator between a header and the `{` of a body, which starts a line of its
+    /// own in the Allman style unless the body is empty
+    fn before_brace(&self, empty: bool) -> Doc {
+        if self.options.brace_style == BraceStyle::Allman && !empty {
+            Doc::hardline()
+        } else {
+            Doc::text(" ")
+        }
+    }
+
+    /// Separator between a `}` and the `else`, `catch` or `finally` after it
+    fn before_continuation(&self) -> Doc {
+        if self.options.brace_style == BraceStyle::OneTbs {
+            Doc::text(" ")
+        } else {
+            Doc::hardline()
+        }
+    }
+
+    /// Check if a block prints as `{}`
+    fn is_empty_block(&self, block: &Block) -> bool {
+        statement_list(&block.body).is_empty() && !self.has_comments_in(block.span)
+    }
+
+    /// Print a block that follows a header such as `try` or a function's parameters
+    fn print_header_block(&mut self, block: &Block) -> Doc {
+        Doc::concat(vec![
+            self.before_brace(self.is_empty_block(block)),
+            self.print_block(block),
+        ])
+    }
+
+    // Comments
+
+    /// Index of the first unprinted comment if it starts before `offset`
+    fn next_comment_before(&mut self, offset: usize) -> Option<usize> {
+        while self.printed.get(self.next_comment) == Some(&true) {
+            self.next_comment += 1;
+        }
+        let comment = self.comments.get(self.next_comment)?;
+        (comment.span.start < offset).then_some(self.next_comment)
+    }
+
+    /// Offset of the first code at or after `offset`, skipping whitespace and comments
+    fn code_after(&self, mut offset: usize) -> usize {
+        loop {
+            let rest = &self.content[offset..];
+            offset += rest.len() - rest.trim_start().len();
+            match self
+                .comments
+                .binary_search_by_key(&offset, |comment| comment.span.start)
+            {
+                Ok(index) => offset = self.comments[index].span.end,
+                Err(_) => return offset,
+            }
+        }
+    }
+
+    /// Offset just past the last code before `offset`, skipping whitespace and comments
+    fn code_before(&self, mut offset: usize) -> usize {
+        loop {
+            offset = self.content[..offset].trim_end().len();
+            match self
+                .comments
+                .binary_search_by_key(&offset, |comment| comment.span.end)
+            {
+                Ok(index) => offset = self.comments[index].span.start,
+                Err(_) => return offset,
+            }
+        }
+    }
+
+    /// Check if an unprinted comment lies within `span`
+    fn has_comments_in(&self, span: Span) -> bool {
+        self.comments
+            .iter()
+            .zip(&self.printed)
+            .skip(self.next_comment)
+            .any(|(comment, printed)| {
+                !printed && comment.span.start > span.start && comment.span.start < span.end
+            })
+    }
+
+    /// Check if the source has an empty line between the last printed line and `offset`
+    fn blank_line_before(&self, offset: usize) -> bool {
+        if offset <= self.last_end {
+            return false;
+        }
+        let between = &self.content[self.last_end..offset];
+        between.trim().is_empty() && line_break_count(between) >= 2
+    }
+
+    /// Add the unprinted comments before `offset` to `lines`, each on a line of its own
+    fn print_leading_comments(&mut self, offset: usize, lines: &mut Vec<Doc>) {
+        while let Some(index) = self.next_comment_before(offset) {
+            let comment = self.comments[index];
+            let blank_line = self.blank_line_before(comment.span.start);
+            push_line(lines, print_comment(comment), blank_line);
+            self.printed[index] = true;
+            self.last_end = self.last_end.max(comment.span.end);
+        }
+    }
+
+    /// Print a comment after the code it follows
+    ///
+    /// Line comments are moved to the end of the line and break the enclosing
+    /// groups, so that no code ever follows them on their line.
+    fn print_trailing_comment(&mut self, index: usize) -> Doc {
+        let comment = self.comments[index];
+        self.printed[index] = true;
+        self.last_end = self.last_end.max(comment.span.end);
+
+        match comment.kind {
+            TokenKind::Comment(CommentKind::Block, _) => {
+                Doc::concat(vec![Doc::text(" "), print_comment(comment)])
+            }
+            _ => Doc::concat(vec![
+                Doc::line_suffix(Doc::concat(vec![Doc::text(" "), print_comment(comment)])),
+                Doc::break_parent(),
+            ]),
+        }
+    }
+
+    /// Print the comments trailing a node that ends at `end`
+    ///
+    /// These are the comments left inside the node, and the comments after it on the
+    /// same line that come before `limit`. A block comment after a separator such as
+    /// `,` is left to lead the next node instead.
+    fn print_trailing_comments(&mut self, end: usize, limit: usize) -> Doc {
+        let mut comments = Vec::new();
+        let mut previous_end = end;
+        while let Some(index) = self.next_comment_before(limit) {
+            let comment = self.comments[index];
+            if comment.span.start >= end {
+                let between = &self.content[previous_end..comment.span.start];
+                let is_block = matches!(comment.kind, TokenKind::Comment(CommentKind::Block, _));
+                if between.contains(is_line_terminator) || (is_block && !between.trim().is_empty())
+                {
+                    break;
+                }
+            }
+            previous_end = previous_end.max(comment.span.end);
+            comments.push(self.print_trailing_comment(index));
+        }
+        Doc::concat(comments)
+    }
+
+    /// Print all comments left before `end` after the last item of a list, as if
+    /// they trailed it
+    fn print_remaining_comments(&mut self, end: usize) -> Doc {
+        let mut comments = Vec::new();
+        while let Some(index) = self.next_comment_before(end) {
+            comments.push(self.print_trailing_comment(index));
+        }
+        Doc::concat(comments)
+    }
+
+    /// Print the items of a list that is closed at `end`, each followed by its
+    /// trailing comments
+    fn print_items<T>(
+        &mut self,
+        items: &[T],
+        span: impl Fn(&T) -> Option<Span>,
+        mut print: impl FnMut(&mut Self, &T) -> Doc,
+        end: usize,
+    ) -> Vec<Doc> {
+        let mut printed = Vec::new();
+        for (index, item) in items.iter().enumerate() {
+            let mut parts = vec![print(self, item)];
+            if let Some(item_span) = span(item) {
+                let limit = items[index + 1..]
+                    .iter()
+                    .find_map(&span)
+                    .map_or(end, |next| next.start);
+                parts.push(self.print_trailing_comments(item_span.end, limit));
+            }
+            if index + 1 == items.len() {
+                parts.push(self.print_remaining_comments(end));
+            }
+            printed.push(Doc::concat(parts));
+        }
+        printed
+    }
+
+    /// Print a comma-separated list that breaks into one item per line if it does not
+    /// fit. An empty list keeps the comments left before `end` between its brackets.
+    fn print_list(
+        &mut self,
+        open: &str,
+        close: &str,
+        items: Vec<Doc>,
+        trailing_comma: bool,
+        end: usize,
+    ) -> Doc {
+        if !items.is_empty() {
+            return list(open, close, items, trailing_comma);
+        }
+
+        let mut comments = Vec::new();
+        let mut inline = true;
+        while let Some(index) = self.next_comment_before(end) {
+            let comment = self.comments[index];
+            inline &= matches!(comment.kind, TokenKind::Comment(CommentKind::Block, _));
+            comments.push(print_comment(comment));
+            self.printed[index] = true;
+            self.last_end = self.last_end.max(comment.span.end);
+        }
+
+        if comments.is_empty() {
+            Doc::text(format!("{}{}", open, close))
+        } else if inline {
+            Doc::concat(vec![
+                Doc::text(open),
+                Doc::join(Doc::text(" "), comments),
+                Doc::text(close),
+            ])
+        } else {
+            Doc::concat(vec![
+                Doc::text(open),
+                Doc::indent(Doc::concat(vec![
+                    Doc::hardline(),
+                    Doc::join(Doc::hardline(), comments),
+                ])),
+                Doc::hardline(),
+                Doc::text(close),
+            ])
+        }
+    }
+
+    /// Print the unprinted comments before `offset` in front of the code that follows
+    ///
+    /// Block comments stay inline, as in `f(/* flag */ true)`; line comments end
+    /// their line.
+    fn print_inline_comments(&mut self, offset: usize) -> Vec<Doc> {
+        let mut parts = Vec::new();
+        while let Some(index) = self.next_comment_before(offset) {
+            let comment = self.comments[index];
+            parts.push(print_comment(comment));
+            let inline = matches!(comment.kind, TokenKind::Comment(CommentKind::Block, _));
+            parts.push(if inline {
+                Doc::text(" ")
+            } else {
+                Doc::hardline()
+            });
+            self.printed[index] = true;
+            self.last_end = self.last_end.max(comment.span.end);
+        }
+        parts
+    }
+
+    /// Print `items` on lines of their own, keeping single empty lines and comments
+    /// between them. Comments left before `end` are printed after the last item.
+    fn print_lines<T>(
+        &mut self,
+        items: &[T],
+        span: impl Fn(&T) -> Span,
+        mut print: impl FnMut(&mut Self, &T, bool) -> Doc,
+        end: usize,
+    ) -> Doc {
+        let mut lines = Vec::new();
+        for (index, item) in items.iter().enumerate() {
+            let item_span = span(item);
+            self.print_leading_comments(item_span.start, &mut lines);
+            let blank_line = self.blank_line_before(item_span.start);
+
+            let is_last = index + 1 == items.len();
+            let doc = print(self, item, is_last);
+            self.last_end = self.last_end.max(item_span.end);
+
+            let limit = items.get(index + 1).map_or(end, |next| span(next).start);
+            let comments = self.print_trailing_comments(item_span.end, limit);
+            push_line(&mut lines, Doc::concat(vec![doc, comments]), blank_line);
+        }
+        self.print_leading_comments(end, &mut lines);
+        Doc::concat(lines)
+    }
+
+    /// Print `items` indented between `open` and `close`, or `open` and `close`
+    /// alone when there is nothing in between
+    fn print_indented_lines<T>(
+        &mut self,
+        open: &str,
+        close: &str,
+        items: &[T],
+        span: Span,
+        item_span: impl Fn(&T) -> Span,
+        print: impl FnMut(&mut Self, &T, bool) -> Doc,
+    ) -> Doc {
+        if items.is_empty() && !self.has_comments_in(span) {
+            return Doc::text(format!("{}{}", open, close));
+        }
+
+        let lines = self.print_lines(items, item_span, print, span.end);
+        Doc::concat(vec![
+            Doc::text(open),
+            Doc::indent(Doc::concat(vec![Doc::hardline(), lines])),
+            Doc::hardline(),
+            Doc::text(close),
+        ])
+    }
+
+    // Statements
+
+    fn print_statement(&mut self, statement: &Statement) -> Doc {
+        match &statement.kind {
+            StatementKind::Expression(expression) => {
+                // Without semicolons, a `;` keeps the statement from joining the
+                // previous line
+                let guard = if !self.options.semicolons && starts_with_asi_hazard(expression) {
+                    Doc::text(";")
+                } else {
+                    Doc::nil()
+                };
+                Doc::concat(vec![
+                    guard,
+                    self.print_expression(expression),
+                    self.semicolon(),
+                ])
+            }
+            StatementKind::VariableDeclaration(declaration) => Doc::concat(vec![
+                self.print_variable_declaration(declaration),
+                self.semicolon(),
+            ]),
+            StatementKind::FunctionDeclaration(function) => self.print_function(function),
+            StatementKind::ClassDeclaration(class) => self.print_class(class),
+            StatementKind::Return(argument) => {
+                let mut parts = vec![Doc::text("return")];
+                if let Some(argument) = argument {
+                    parts.push(Doc::text(" "));
+                    parts.push(self.print_return_argument(argument));
+                }
+                parts.push(self.semicolon());
+                Doc::concat(parts)
+            }
+            StatementKind::If {
+                test,
+                consequent,
+                alternate,
+            } => {
+                let header = Doc::concat(vec![
+                    Doc::text("if ("),
+                    self.print_test(test),
+                    Doc::text(")"),
+                    self.print_body(consequent),
+                ]);
+                let mut parts = vec![Doc::group(header)];
+                if let Some(alternate) = alternate {
+                    if matches!(consequent.kind, StatementKind::Block(_)) {
+                        parts.push(self.before_continuation());
+                    } else {
+                        parts.push(Doc::hardline());
+                    }
+                    parts.push(Doc::text("else"));
+                    if matches!(alternate.kind, StatementKind::If { .. }) {
+                        parts.push(Doc::text(" "));
+                        parts.push(self.print_statement(alternate));
+                    } else {
+                        parts.push(Doc::group(self.print_body(alternate)));
+                    }
+                }
+                Doc::concat(parts)
+            }
+            StatementKind::For {
+                init,
+                test,
+                update,
+                body,
+            } => {
+                let header = if init.is_none() && test.is_none() && update.is_none() {
+                    Doc::text(";;")
+                } else {
+                    let init = match init {
+                        Some(init) => self.print_for_init(init),
+                        None => Doc::nil(),
+                    };
+                    let test = match test {
+                        Some(test) => self.print_expression(test),
+                        None => Doc::nil(),
+                    };
+                    let update = match update {
+                        Some(update) => self.print_expression(update),
+                        None => Doc::nil(),
+                    };
+                    Doc::group(Doc::concat(vec![
+                        Doc::indent(Doc::concat(vec![
+                            Doc::softline(),
+                            init,
+                            Doc::text(";"),
+                            Doc::line(),
+                            test,
+                            Doc::text(";"),
+                            Doc::line(),
+                            update,
+                        ])),
+                        Doc::softline(),
+                    ]))
+                };
+                Doc::group(Doc::concat(vec![
+                    Doc::text("for ("),
+                    header,
+                    Doc::text(")"),
+                    self.print_body(body),
+                ]))
+            }
+            StatementKind::ForIn { left, right, body } => Doc::group(Doc::concat(vec![
+                Doc::text("for ("),
+                self.print_for_init(left),
+                Doc::text(" in "),
+                self.print_expression(right),
+                Doc::text(")"),
+                self.print_body(body),
+            ])),
+            StatementKind::ForOf {
+                is_await,
+                left,
+                right,
+                body,
+            } => Doc::group(Doc::concat(vec![
+                Doc::text(if *is_await { "for await (" } else { "for (" }),
+                self.print_for_init(left),
+                Doc::text(" of "),
+                self.print_expression(right),
+                Doc::text(")"),
+                self.print_body(body),
+            ])),
+            StatementKind::While { test, body } => Doc::group(Doc::concat(vec![
+                Doc::text("while ("),
+                self.print_test(test),
+                Doc::text(")"),
+                self.print_body(body),
+            ])),
+            StatementKind::With { object, body } => Doc::group(Doc::concat(vec![
+                Doc::text("with ("),
+                self.print_test(object),
+                Doc::text(")"),
+                self.print_body(body),
+            ])),
+            StatementKind::DoWhile { body, test } => {
+                let cuddled = matches!(body.kind, StatementKind::Block(_))
+                    && self.options.brace_style != BraceStyle::Allman;
+                let separator = if cuddled {
+                    Doc::text(" ")
+                } else {
+                    Doc::hardline()
+                };
+                Doc::concat(vec![
+                    Doc::group(Doc::concat(vec![Doc::text("do"), self.print_body(body)])),
+                    separator,
+                    Doc::text("while ("),
+                    self.print_test(test),
+                    Doc::text(")"),
+                    self.semicolon(),
+                ])
+            }
+            StatementKind::Block(block) => self.print_block(block),
+            StatementKind::Empty => Doc::text(";"),
+            StatementKind::Break(label) | StatementKind::Continue(label) => {
+                let keyword = match statement.kind {
+                    StatementKind::Break(_) => "break",
+                    _ => "continue",
+                };
+                let mut parts = vec![Doc::text(keyword)];
+                if let Some(label) = label {
+                    parts.push(Doc::text(format!(" {}", label)));
+                }
+                parts.push(self.semicolon());
+                Doc::concat(parts)
+            }
+            StatementKind::Throw(argument) => Doc::concat(vec![
+                Doc::text("throw "),
+                self.print_return_argument(argument),
+                self.semicolon(),
+            ]),
+            StatementKind::Try {
+                block,
+                handler,
+                finalizer,
+            } => {
+                let mut parts = vec![Doc::text("try"), self.print_header_block(block)];
+                if let Some(handler) = handler {
+                    parts.push(self.before_continuation());
+                    parts.push(Doc::text("catch"));
+                    if let Some(param) = &handler.param {
+                        parts.push(Doc::text(" ("));
+                        parts.push(self.print_expression(param));
+                        parts.push(Doc::text(")"));
+                    }
+                    parts.push(self.print_header_block(&handler.body));
+                }
+                if let Some(finalizer) = finalizer {
+                    parts.push(self.before_continuation());
+                    parts.push(Doc::text("finally"));
+                    parts.push(self.print_header_block(finalizer));
+                }
+                Doc::concat(parts)
+            }
+            StatementKind::Switch {
+                discriminant,
+                cases,
+            } => {
+                let empty = cases.is_empty() && !self.has_comments_in(statement.span);
+                let header = Doc::group(Doc::concat(vec![
+                    Doc::text("switch ("),
+                    self.print_test(discriminant),
+                    Doc::text(")"),
+                ]));
+                let header = Doc::concat(vec![header, self.before_brace(empty)]);
+                let body = self.print_indented_lines(
+                    "{",
+                    "}",
+                    cases,
+                    statement.span,
+                    |case| case.span,
+                    |printer, case, is_last| {
+                        let end = cases
+                            .iter()
+                            .map(|case| case.span.start)
+                            .find(|&start| start > case.span.start)
+                            .unwrap_or(statement.span.end);
+                        printer.print_switch_case(case, end, is_last)
+                    },
+                );
+                Doc::concat(vec![header, body])
+            }
+            StatementKind::Labeled { label, body } => Doc::concat(vec![
+                Doc::text(format!("{}: ", label)),
+                self.print_statement(body),
+            ]),
+            StatementKind::Import(import) => self.print_import(import),
+            StatementKind::Export(export) => self.print_export(export, statement.span.end),
+            StatementKind::Debugger => Doc::concat(vec![Doc::text("debugger"), self.semicolon()]),
+        }
+    }
+
+    /// Print the body of a control statement after its header
+    ///
+    /// Blocks stay on the header's line, other statements move to an indented line
+    /// of their own when the header's group breaks.
+    fn print_body(&mut self, body: &Statement) -> Doc {
+        match body.kind {
+            StatementKind::Empty => Doc::text(";"),
+            StatementKind::Block(ref block) => self.print_header_block(block),
+            _ => Doc::indent(Doc::concat(vec![Doc::line(), self.print_statement(body)])),
+        }
+    }
+
+    /// Print the parenthesized test of a control statement, which moves to lines of
+    /// its own if it does not fit. Comments before the `)` trail the test.
+    fn print_test(&mut self, test: &Expression) -> Doc {
+        let close = self.code_after(test.span.end);
+        let test = Doc::concat(vec![
+            self.print_unindented(test),
+            self.print_remaining_comments(close),
+        ]);
+        Doc::group(Doc::concat(vec![
+            Doc::indent(Doc::concat(vec![Doc::softline(), test])),
+            Doc::softline(),
+        ]))
+    }
+
+    /// Print the argument of `return` or `throw`, wrapping it in parentheses when a
+    /// binary expression has to break
+    fn print_return_argument(&mut self, argument: &Expression) -> Doc {
+        // Parentheses around a binary argument are printed only when it breaks
+        let argument = match &argument.kind {
+            ExpressionKind::Parenthesized(inner)
+                if matches!(inner.kind, ExpressionKind::Binary { .. })
+                    && !self.has_comments_in(argument.span) =>
+            {
+                inner
+            }
+            _ => argument,
+        };
+        if !matches!(argument.kind, ExpressionKind::Binary { .. }) {
+            return self.print_expression(argument);
+        }
+
+        let argument = self.print_unindented(argument);
+        Doc::group(Doc::concat(vec![
+            Doc::if_break(Doc::text("("), Doc::nil()),
+            Doc::indent(Doc::concat(vec![Doc::softline(), argument])),
+            Doc::softline(),
+            Doc::if_break(Doc::text(")"), Doc::nil()),
+        ]))
+    }
+
+    fn print_block(&mut self, block: &Block) -> Doc {
+        self.print_indented_lines(
+            "{",
+            "}",
+            &statement_list(&block.body),
+            block.span,
+            |statement| statement.span,
+            |printer, statement, _| printer.print_statement(statement),
+        )
+    }
+
+    fn print_variable_declaration(&mut self, declaration: &VariableDeclaration) -> Doc {
+        let declarators: Vec<Doc> = declaration
+            .declarations
+            .iter()
+            .map(|declarator| {
+                let id = self.print_expression(&declarator.id);
+                match &declarator.init {
+                    Some(init) => self.print_assignment(id, " =", init),
+                    None => id,
+                }
+            })
+            .collect();
+
+        // Declarators go on lines of their own as soon as one of them is initialized
+        let has_init = declaration
+            .declarations
+            .iter()
+            .any(|declarator| declarator.init.is_some());
+        let separator = if has_init && declarators.len() > 1 {
+            Doc::hardline()
+        } else {
+            Doc::line()
+        };
+
+        let mut declarators = declarators.into_iter();
+        let first = declarators.next().unwrap_or_else(Doc::nil);
+        let rest: Vec<Doc> = declarators
+            .map(|declarator| Doc::concat(vec![Doc::text(","), separator.clone(), declarator]))
+            .collect();
+        Doc::group(Doc::concat(vec![
+            Doc::text(format!("{} ", declaration.kind)),
+            first,
+            Doc::indent(Doc::concat(rest)),
+        ]))
+    }
+
+    /// Print `left`, `operator` and `right`, moving a binary `right` to the next line
+    /// if it does not fit
+    fn print_assignment(&mut self, left: Doc, operator: &str, right: &Expression) -> Doc {
+        // Binary expressions, and conditionals testing one, move to the next line as a
+        // whole before breaking themselves
+        let break_after_operator = match &right.kind {
+            ExpressionKind::Binary { .. } => true,
+            ExpressionKind::Conditional { test, .. } => {
+                matches!(test.kind, ExpressionKind::Binary { .. })
+            }
+            _ => false,
+        };
+        if !break_after_operator {
+            return Doc::concat(vec![
+                left,
+                Doc::text(format!("{} ", operator)),
+                self.print_expression(right),
+            ]);
+        }
+
+        let right = self.print_unindented(right);
+        Doc::group(Doc::concat(vec![
+            left,
+            Doc::text(operator),
+            Doc::group(Doc::indent(Doc::concat(vec![Doc::line(), right]))),
+        ]))
+    }
+
+    fn print_for_init(&mut self, init: &ForInit) -> Doc {
+        match init {
+            ForInit::VariableDeclaration(declaration) => {
+                self.print_variable_declaration(declaration)
+            }
+            ForInit::Expression(expression) => self.print_expression(expression),
+        }
+    }
+
+    /// Print a `case` or `default` clause, which is followed by the next clause or the
+    /// end of the `switch` at `end`
+    ///
+    /// Comments on their own lines in an empty `default` or last clause are indented
+    /// inside it, as its statements would be.
+    fn print_switch_case(&mut self, case: &SwitchCase, end: usize, is_last: bool) -> Doc {
+        let (mut parts, label_end) = match &case.test {
+            Some(test) => (
+                vec![Doc::text("case "), self.print_expression(test)],
+                test.span.end,
+            ),
+            None => (
+                vec![Doc::text("default")],
+                case.span.start + "default".len(),
+            ),
+        };
+        let limit = case
+            .consequent
+            .first()
+            .map_or(end, |statement| statement.span.start);
+        let colon = self.code_after(label_end);
+        parts.push(Doc::text(":"));
+        parts.push(self.print_trailing_comments(colon + 1, limit));
+
+        match statement_list(&case.consequent).as_slice() {
+            [] if case.test.is_none() || is_last => {
+                let mut comments = Vec::new();
+                self.print_leading_comments(end, &mut comments);
+                if !comments.is_empty() {
+                    parts.push(Doc::indent(Doc::concat(vec![
+                        Doc::hardline(),
+                        Doc::concat(comments),
+                    ])));
+                }
+            }
+            [] => {}
+            [Statement {
+                kind: StatementKind::Block(block),
+                ..
+            }] => {
+                parts.push(Doc::text(" "));
+                parts.push(self.print_block(block));
+            }
+            consequent => {
+                let lines = self.print_lines(
+                    consequent,
+                    |statement| statement.span,
+                    |printer, statement, _| printer.print_statement(statement),
+                    case.span.end,
+                );
+                parts.push(Doc::indent(Doc::concat(vec![Doc::hardline(), lines])));
+            }
+        }
+        Doc::concat(parts)
+    }
+
+    fn print_import(&mut self, import: &ImportDeclaration) -> Doc {
+        let mut parts = vec![Doc::text("import ")];
+        if import.default.is_some() || import.namespace.is_some() || import.named.is_some() {
+            let mut clauses = Vec::new();
+            if let Some(default) = &import.default {
+                clauses.push(Doc::text(default.as_str()));
+            }
+            if let Some(namespace) = &import.namespace {
+                clauses.push(Doc::text(format!("* as {}", namespace)));
+            }
+            if let Some(named) = &import.named {
+                clauses.push(self.print_module_specifiers(named, import.span.end));
+            }
+            parts.push(Doc::join(Doc::text(", "), clauses));
+            parts.push(Doc::text(" from "));
+        }
+        parts.push(self.print_string(&import.source));
+        parts.push(self.semicolon());
+        Doc::concat(parts)
+    }
+
+    /// Print an export declaration that ends at `end`
+    fn print_export(&mut self, export: &ExportDeclaration, end: usize) -> Doc {
+        let mut parts = vec![Doc::text("export ")];
+        match export {
+            ExportDeclaration::Named { specifiers, source } => {
+                parts.push(self.print_module_specifiers(specifiers, end));
+                if let Some(source) = source {
+                    parts.push(Doc::text(" from "));
+                    parts.push(self.print_string(source));
+                }
+                parts.push(self.semicolon());
+            }
+            ExportDeclaration::All { alias, source } => {
+                parts.push(Doc::text("*"));
+                if let Some(alias) = alias {
+                    parts.push(Doc::text(format!(" as {}", alias)));
+                }
+                parts.push(Doc::text(" from "));
+                parts.push(self.print_string(source));
+                parts.push(self.semicolon());
+            }
+            ExportDeclaration::Declaration(declaration) => {
+                parts.push(self.print_statement(declaration));
+            }
+            ExportDeclaration::DefaultDeclaration(declaration) => {
+                parts.push(Doc::text("default "));
+                parts.push(self.print_statement(declaration));
+            }
+            ExportDeclaration::DefaultExpression(expression) => {
+                parts.push(Doc::text("default "));
+                parts.push(self.print_expression(expression));
+                parts.push(self.semicolon());
+            }
+        }
+        Doc::concat(parts)
+    }
+
+    /// Print the specifiers of a module declaration that ends at `end`
+    fn print_module_specifiers(&mut self, specifiers: &[ModuleSpecifier], end: usize) -> Doc {
+        if specifiers.is_empty() {
+            return Doc::text("{}");
+        }
+
+        let specifiers: Vec<Doc> = specifiers
+            .iter()
+            .enumerate()
+            .map(|(index, specifier)| {
+                let mut parts = self.print_inline_comments(specifier.span.start);
+                parts.push(self.print_module_name(&specifier.name));
+                if let Some(alias) = &specifier.alias {
+                    parts.push(Doc::text(" as "));
+                    parts.push(self.print_module_name(alias));
+                }
+                let limit = specifiers
+                    .get(index + 1)
+                    .map_or(end, |next| next.span.start);
+                parts.push(self.print_trailing_comments(specifier.span.end, limit));
+                Doc::concat(parts)
+            })
+            .collect();
+        let trailing_comma = if self.options.trailing_commas != TrailingCommas::None {
+            Doc::if_break(Doc::text(","), Doc::nil())
+        } else {
+            Doc::nil()
+        };
+
+        Doc::group(Doc::concat(vec![
+            Doc::text("{"),
+            Doc::indent(Doc::concat(vec![
+                Doc::line(),
+                Doc::join(Doc::concat(vec![Doc::text(","), Doc::line()]), specifiers),
+                trailing_comma,
+            ])),
+            Doc::line(),
+            Doc::text("}"),
+        ]))
+    }
+
+    /// Print a module export name, which is either an identifier or a string
+    fn print_module_name(&self, name: &str) -> Doc {
+        if name.starts_with(['"', '\'']) {
+            self.print_string(name)
+        } else {
+            Doc::text(name)
+        }
+    }
+
+    // Functions and classes
+
+    fn print_function(&mut self, function: &Function) -> Doc {
+        let mut parts = Vec::new();
+        if function.is_async {
+            parts.push(Doc::text("async "));
+        }
+        parts.push(Doc::text("function"));
+        if function.is_generator {
+            parts.push(Doc::text("*"));
+        }
+        parts.push(Doc::text(" "));
+        if let Some(name) = &function.name {
+            parts.push(Doc::text(name.as_str()));
+        }
+        parts.push(self.print_params_and_body(function));
+        Doc::concat(parts)
+    }
+
+    /// Print the parameters and body of a function. Comments between the `)` and the
+    /// `{` trail the parameters, except that a line comment moves into a non-empty body.
+    fn print_params_and_body(&mut self, function: &Function) -> Doc {
+        let close = self.code_before(function.body.span.start) - 1;
+        let mut parts = vec![self.print_params(&function.params, close)];
+        let empty = self.is_empty_block(&function.body);
+        while let Some(index) = self.next_comment_before(function.body.span.start) {
+            let comment = self.comments[index];
+            if !empty && !matches!(comment.kind, TokenKind::Comment(CommentKind::Block, _)) {
+                break;
+            }
+            parts.push(self.print_trailing_comment(index));
+        }
+        parts.push(self.print_header_block(&function.body));
+        Doc::concat(parts)
+    }
+
+    /// Print parameters whose closing parenthesis comes before `end`
+    fn print_params(&mut self, params: &[Expression], end: usize) -> Doc {
+        // A lone destructuring pattern hugs the parentheses
+        if let [param] = params {
+            if is_pattern(param) {
+                return Doc::concat(vec![
+                    Doc::text("("),
+                    self.print_param(param),
+                    Doc::text(")"),
+                ]);
+            }
+        }
+
+        let trailing_comma =
+            self.options.trailing_commas == TrailingCommas::All && !is_spread(params.last());
+        let params = self.print_items(params, |param| Some(param.span), Self::print_param, end);
+        self.print_list("(", ")", params, trailing_comma, end)
+    }
+
+    /// Print a parameter. Unlike other objects, a destructured parameter stays on one
+    /// line when it fits, even if the source breaks after its `{`.
+    fn print_param(&mut self, param: &Expression) -> Doc {
+        let ExpressionKind::Object(properties) = &param.kind else {
+            return self.print_expression(param);
+        };
+        let mut parts = self.print_inline_comments(param.span.start);
+        parts.push(self.print_object(properties, param.span, false));
+        Doc::concat(parts)
+    }
+
+    /// Print a method's modifiers, name, parameters and body
+    fn print_method(&mut self, kind: MethodKind, key: &PropertyKey, function: &Function) -> Doc {
+        let mut parts = Vec::new();
+        match kind {
+            MethodKind::Getter => parts.push(Doc::text("get ")),
+            MethodKind::Setter => parts.push(Doc::text("set ")),
+            MethodKind::Method | MethodKind::Constructor => {}
+        }
+        if function.is_async {
+            parts.push(Doc::text("async "));
+        }
+        if function.is_generator {
+            parts.push(Doc::text("*"));
+        }
+        parts.push(self.print_property_key(key));
+        parts.push(self.print_params_and_body(function));
+        Doc::concat(parts)
+    }
+
+    fn print_class(&mut self, class: &Class) -> Doc {
+        let mut parts = vec![Doc::text("class")];
+        if let Some(name) = &class.name {
+            parts.push(Doc::text(format!(" {}", name)));
+        }
+        if let Some(super_class) = &class.super_class {
+            parts.push(Doc::text(" extends "));
+            parts.push(self.print_expression(super_class));
+        }
+
+        let members: Vec<&ClassMember> = class
+            .body
+            .iter()
+            .filter(|member| !matches!(member.kind, ClassMemberKind::Empty))
+            .collect();
+        parts.push(self.before_brace(members.is_empty() && !self.has_comments_in(class.span)));
+        let members: Vec<(&ClassMember, Option<&ClassMember>)> = members
+            .iter()
+            .enumerate()
+            .map(|(index, member)| (*member, members.get(index + 1).copied()))
+            .collect();
+        parts.push(self.print_indented_lines(
+            "{",
+            "}",
+            &members,
+            class.span,
+            |(member, _)| member.span,
+            |printer, (member, next), _| printer.print_class_member(member, *next),
+        ));
+        Doc::concat(parts)
+    }
+
+    fn print_class_member(&mut self, member: &ClassMember, next: Option<&ClassMember>) -> Doc {
+        let (is_static, member) = match &member.kind {
+            ClassMemberKind::Method {
+                is_static,
+                kind,
+                key,
+                function,
+            } => (*is_static, self.print_method(*kind, key, function)),
+            ClassMemberKind::Field {
+                is_static,
+                key,
+                value,
+            } => {
+                let key = self.print_property_key(key);
+                let field = match value {
+                    Some(value) => self.print_assignment(key, " =", value),
+                    None => key,
+                };
+                let semicolon = if self.options.semicolons || field_needs_semicolon(member, next) {
+                    Doc::text(";")
+                } else {
+                    Doc::nil()
+                };
+                (*is_static, Doc::concat(vec![field, semicolon]))
+            }
+            ClassMemberKind::StaticBlock(block) => (
+                false,
+                Doc::concat(vec![Doc::text("static"), self.print_header_block(block)]),
+            ),
+            ClassMemberKind::Empty => (false, Doc::nil()),
+        };
+
+        if is_static {
+            Doc::concat(vec![Doc::text("static "), member])
+        } else {
+            member
+        }
+    }
+
+    fn print_property_key(&mut self, key: &PropertyKey) -> Doc {
+        match key {
+            PropertyKey::Identifier(name) | PropertyKey::Private(name) => Doc::text(name.as_str()),
+            PropertyKey::Number(raw) => Doc::text(self.print_number(raw)),
+            PropertyKey::String(raw) => self.print_string(raw),
+            PropertyKey::Computed(expression) => Doc::concat(vec![
+                Doc::text("["),
+                self.print_expression(expression),
+                Doc::text("]"),
+            ]),
+        }
+    }
+
+    // Expressions
+
+    fn print_each(&mut self, expressions: &[Expression]) -> Vec<Doc> {
+        expressions
+            .iter()
+            .map(|expression| self.print_expression(expression))
+            .collect()
+    }
+
+    /// Print a numeric literal, normalized if the options ask for it
+    fn print_number(&self, raw: &str) -> String {
+        if self.options.normalize_numbers {
+            normalize_number(raw)
+        } else {
+            raw.to_string()
+        }
+    }
+
+    /// Print an object literal or pattern. It stays on one line if it fits, unless it
+    /// holds comments or, when `keep_break` is set, the source has a line break between
+    /// the `{` and the first property, as Prettier does.
+    fn print_object(&mut self, properties: &[Property], span: Span, keep_break: bool) -> Doc {
+        // A rest element must come last, so it cannot take a trailing comma
+        let trailing_comma = self.options.trailing_commas != TrailingCommas::None
+            && !properties
+                .last()
+                .is_some_and(|property| matches!(property.kind, PropertyKind::Spread(_)));
+
+        let expanded = properties.first().is_some_and(|first| {
+            keep_break && self.content[span.start..first.span.start].contains(is_line_terminator)
+        });
+        if properties.is_empty() || expanded || self.has_comments_in(span) {
+            return self.print_indented_lines(
+                "{",
+                "}",
+                properties,
+                span,
+                |property| property.span,
+                |printer, property, is_last| {
+                    let property = printer.print_property(property);
+                    if !is_last || trailing_comma {
+                        Doc::concat(vec![property, Doc::text(",")])
+                    } else {
+                        property
+                    }
+                },
+            );
+        }
+
+        let properties: Vec<Doc> = properties
+            .iter()
+            .map(|property| self.print_property(property))
+            .collect();
+        let trailing_comma = if trailing_comma {
+            Doc::if_break(Doc::text(","), Doc::nil())
+        } else {
+            Doc::nil()
+        };
+        Doc::group(Doc::concat(vec![
+            Doc::text("{"),
+            Doc::indent(Doc::concat(vec![
+                Doc::line(),
+                Doc::join(Doc::concat(vec![Doc::text(","), Doc::line()]), properties),
+                trailing_comma,
+            ])),
+            Doc::line(),
+            Doc::text("}"),
+        ]))
+    }
+
+    /// Print a string literal from its raw source text with the configured quotes
+    fn print_string(&self, raw: &str) -> Doc {
+        let original = raw.chars().next().unwrap_or('"');
+        let contents = &raw[1..raw.len() - 1];
+        let quote = match self.options.quote_style.as_char() {
+            Some(preferred) => choose_quote(contents, preferred),
+            None => original,
+        };
+        Doc::text(quote_string(&normalize_line_breaks(contents), quote))
+    }
+
+    /// Print a template literal, keeping its text apart from line breaks and formatting the
+    /// expressions of its substitutions on one line each. An expression that has to
+    /// break, such as a function with a block body, is indented with the code around the
+    /// template.
+    fn print_template(&mut self, span: Span, quasis: &[String], expressions: &[Expression]) -> Doc {
+        // Comments inside the substitutions could swallow the closing `}`
+        if self.has_comments_in(span) {
+            for index in self.next_comment..self.comments.len() {
+                if self.comments[index].span.start < span.end {
+                    self.printed[index] = true;
+                }
+            }
+            return Doc::text(normalize_line_breaks(span.text(self.content)));
+        }
+
+        let options = FormatOptions {
+            print_width: isize::MAX as usize,
+            ..self.options.clone()
+        };
+        let mut parts = Vec::new();
+        for (index, quasi) in quasis.iter().enumerate() {
+            parts.push(Doc::text(normalize_line_breaks(quasi)));
+            if let Some(expression) = expressions.get(index) {
+                let expression = self.print_expression(expression);
+                if expression.forces_break() {
+                    parts.push(expression);
+                } else {
+                    parts.push(Doc::text(expression.print(&options)));
+                }
+            }
+        }
+        Doc::concat(parts)
+    }
+
+    fn print_expression(&mut self, expression: &Expression) -> Doc {
+        let mut parts = self.print_inline_comments(expression.span.start);
+        if parts.is_empty() {
+            return self.print_expression_kind(expression);
+        }
+        parts.push(self.print_expression_kind(expression));
+        Doc::concat(parts)
+    }
+
+    fn print_expression_kind(&mut self, expression: &Expression) -> Doc {
+        match &expression.kind {
+            ExpressionKind::Identifier(name) => Doc::text(name.as_str()),
+            ExpressionKind::Literal(raw) if is_number(expression) => {
+                Doc::text(self.print_number(raw))
+            }
+            ExpressionKind::Literal(raw) => Doc::text(raw.as_str()),
+            ExpressionKind::String(raw) => self.print_string(raw),
+            ExpressionKind::Template {
+                quasis,
+                expressions,
+            } => self.print_template(expression.span, quasis, expressions),
+            ExpressionKind::TaggedTemplate { tag, quasi } => Doc::concat(vec![
+                self.print_expression(tag),
+                self.print_expression(quasi),
+            ]),
+            ExpressionKind::This => Doc::text("this"),
+            ExpressionKind::Super => Doc::text("super"),
+            ExpressionKind::Array(elements) => self.print_array(elements, expression.span.end),
+            ExpressionKind::Object(properties) => {
+                self.print_object(properties, expression.span, true)
+            }
+            ExpressionKind::Function(function) => self.print_function(function),
+            ExpressionKind::ArrowFunction(arrow) => self.print_arrow(arrow),
+            ExpressionKind::Class(class) => self.print_class(class),
+            ExpressionKind::Unary { operator, argument } => {
+                let needs_space = match &argument.kind {
+                    // `typeof x`, `void 0`, `delete a.b`
+                    _ if operator.starts_with(char::is_alphabetic) => true,
+                    // `- -x` and `+ ++x` must not run together
+                    ExpressionKind::Unary {
+                        operator: inner, ..
+                    }
+                    | ExpressionKind::Update {
+                        operator: inner,
+                        prefix: true,
+                        ..
+                    } => {
+                        (operator == "-" || operator == "+") && inner.starts_with(operator.as_str())
+                    }
+                    _ => false,
+                };
+                let operator = if needs_space {
+                    format!("{} ", operator)
+                } else {
+                    operator.clone()
+                };
+                Doc::concat(vec![Doc::text(operator), self.print_expression(argument)])
+            }
+            ExpressionKind::Update {
+                operator,
+                prefix,
+                argument,
+            } => {
+                let argument = self.print_expression(argument);
+                if *prefix {
+                    Doc::concat(vec![Doc::text(operator.as_str()), argument])
+                } else {
+                    Doc::concat(vec![argument, Doc::text(operator.as_str())])
+                }
+            }
+            ExpressionKind::Binary { .. } => {
+                let mut parts = self.print_binary(expression);
+                let first = parts.remove(0);
+                Doc::group(Doc::concat(vec![first, Doc::indent(Doc::concat(parts))]))
+            }
+            ExpressionKind::Assignment {
+                operator,
+                left,
+                right,
+            } => {
+                let left = self.print_expression(left);
+                self.print_assignment(left, &format!(" {}", operator), right)
+            }
+            ExpressionKind::Conditional {
+                test,
+                consequent,
+                alternate,
+            } => Doc::group(self.print_conditional(test, consequent, alternate)),
+            ExpressionKind::Call {
+                callee,
+                arguments,
+                optional,
+            } => {
+                // Comments before the `(` trail the callee
+                let open = self.code_after(callee.span.end);
+                Doc::concat(vec![
+                    self.print_expression(callee),
+                    self.print_remaining_comments(open),
+                    Doc::text(if *optional { "?." } else { "" }),
+                    self.print_arguments(arguments, expression.span.end),
+                ])
+            }
+            ExpressionKind::New { callee, arguments } => Doc::concat(vec![
+                Doc::text("new "),
+                self.print_expression(callee),
+                self.print_arguments(
+                    arguments.as_deref().unwrap_or_default(),
+                    expression.span.end,
+                ),
+            ]),
+            ExpressionKind::Member {
+                object,
+                property,
+                computed,
+                optional,
+            } => {
+                // Normalizing `1.0.toFixed()` would leave `1.toFixed()`, where the dot
+                // belongs to the number
+                let bare_integer = !computed
+                    && !optional
+                    && is_number(object)
+                    && matches!(&object.kind, ExpressionKind::Literal(raw)
+                        if self.print_number(raw).bytes().all(|b| b.is_ascii_digit() || b == b'_'));
+                // Comments before the property name, or before the `[`, trail the object
+                let link_start = if *computed {
+                    self.code_after(object.span.end)
+                } else {
+                    property.span.start
+                };
+                let object = self.print_expression(object);
+                let object = if bare_integer {
+                    Doc::concat(vec![Doc::text("("), object, Doc::text(")")])
+                } else {
+                    object
+                };
+                let comments = self.print_remaining_comments(link_start);
+                let property = self.print_expression(property);
+                let link = match (*optional, *computed) {
+                    (false, true) => Doc::concat(vec![Doc::text("["), property, Doc::text("]")]),
+                    (true, true) => Doc::concat(vec![Doc::text("?.["), property, Doc::text("]")]),
+                    (false, false) => Doc::concat(vec![Doc::text("."), property]),
+                    (true, false) => Doc::concat(vec![Doc::text("?."), property]),
+                };
+                // A line comment after the object leaves the link on the next line
+                if comments.forces_break() && !*computed {
+                    Doc::concat(vec![
+                        object,
+                        comments,
+                        Doc::indent(Doc::concat(vec![Doc::hardline(), link])),
+                    ])
+                } else {
+                    Doc::concat(vec![object, comments, link])
+                }
+            }
+            ExpressionKind::MetaProperty { meta, property } => {
+                Doc::text(format!("{}.{}", meta, property))
+            }
+            ExpressionKind::Sequence(expressions) => {
+                let expressions = self.print_each(expressions);
+                Doc::join(Doc::text(", "), expressions)
+            }
+            ExpressionKind::Spread(argument) => {
+                Doc::concat(vec![Doc::text("..."), self.print_expression(argument)])
+            }
+            ExpressionKind::Yield { argument, delegate } => {
+                let mut parts = vec![Doc::text(if *delegate { "yield*" } else { "yield" })];
+                if let Some(argument) = argument {
+                    parts.push(Doc::text(" "));
+                    parts.push(self.print_expression(argument));
+                }
+                Doc::concat(parts)
+            }
+            ExpressionKind::Await(argument) => {
+                Doc::concat(vec![Doc::text("await "), self.print_expression(argument)])
+            }
+            ExpressionKind::Parenthesized(expression) => {
+                let comments = self.print_inline_comments(expression.span.start);
+                // A line comment after the `(` moves the expression to lines of its own
+                if comments.iter().any(Doc::forces_break) {
+                    let inner = self.print_unindented(expression);
+                    Doc::concat(vec![
+                        Doc::text("("),
+                        Doc::indent(Doc::concat(vec![
+                            Doc::hardline(),
+                            Doc::concat(comments),
+                            inner,
+                        ])),
+                        Doc::hardline(),
+                        Doc::text(")"),
+                    ])
+                } else {
+                    Doc::concat(vec![
+                        Doc::text("("),
+                        Doc::concat(comments),
+                        self.print_expression(expression),
+                        Doc::text(")"),
+                    ])
+                }
+            }
+        }
+    }
+
+    /// Print an expression whose surroundings already indent it when it breaks, so
+    /// that the operands of a binary expression line up
+    fn print_unindented(&mut self, expression: &Expression) -> Doc {
+        match expression.kind {
+            ExpressionKind::Binary { .. } => Doc::group(Doc::concat(self.print_binary(expression))),
+            _ => self.print_expression(expression),
+        }
+    }
+
+    /// Print the operands of a binary expression, flattening chains of operators with
+    /// the same precedence. Each operand after the first starts with its operator and
+    /// a line break.
+    fn print_binary(&mut self, expression: &Expression) -> Vec<Doc> {
+        let ExpressionKind::Binary {
+            operator,
+            left,
+            right,
+        } = &expression.kind
+        else {
+            return vec![self.print_expression(expression)];
+        };
+
+        let mut parts = match &left.kind {
+            ExpressionKind::Binary {
+                operator: left_operator,
+                ..
+            } if should_flatten(operator, left_operator) => self.print_binary(left),
+            _ => vec![self.print_expression(left)],
+        };
+        // Comments before the operator trail the left operand, and comments after it on
+        // its line trail the operator
+        let operator_start = self.code_after(left.span.end);
+        let operator_end = operator_start + operator.len();
+        parts.push(Doc::concat(vec![
+            self.print_remaining_comments(operator_start),
+            Doc::text(format!(" {}", operator)),
+            self.print_trailing_comments(operator_end, right.span.start),
+            Doc::line(),
+            self.print_expression(right),
+        ]));
+        parts
+    }
+
+    /// Print call arguments whose closing parenthesis comes before `end`
+    fn print_arguments(&mut self, arguments: &[Expression], end: usize) -> Doc {
+        // Expand a last callback or object in place, as in `it("works", () => {})`,
+        // or a first callback followed by simple arguments, as in `setTimeout(() => {}, 10)`
+        let hug_last = arguments.last().is_some_and(is_huggable)
+            && !arguments[..arguments.len() - 1].iter().any(is_huggable);
+        let hug_first = arguments.len() > 1
+            && is_huggable(&arguments[0])
+            && arguments[1..].iter().all(is_simple);
+
+        let printed = self.print_items(
+            arguments,
+            |argument| Some(argument.span),
+            Self::print_expression,
+            end,
+        );
+        if hug_last || hug_first {
+            return Doc::concat(vec![
+                Doc::text("("),
+                Doc::join(Doc::text(", "), printed),
+                Doc::text(")"),
+            ]);
+        }
+
+        let trailing_comma =
+            self.options.trailing_commas == TrailingCommas::All && !is_spread(arguments.last());
+        self.print_list("(", ")", printed, trailing_comma, end)
+    }
+
+    /// Print the elements of an array whose closing bracket comes before `end`
+    fn print_array(&mut self, elements: &[Option<Expression>], end: usize) -> Doc {
+        let mut printed = self.print_items(
+            elements,
+            |element| element.as_ref().map(|element| element.span),
+            |printer, element| match element {
+                Some(element) => printer.print_expression(element),
+                None => Doc::nil(),
+            },
+            end,
+        );
+
+        // A trailing hole needs its own comma
+        if elements.last().is_some_and(Option::is_none) {
+            let hole = printed.pop().unwrap_or_else(Doc::nil);
+            printed.push(Doc::concat(vec![hole, Doc::text(",")]));
+            return list("[", "]", printed, false);
+        }
+
+        let trailing_comma = self.options.trailing_commas != TrailingCommas::None
+            && !is_spread(elements.last().and_then(Option::as_ref));
+
+        // Numbers fill each line instead of taking a line each, unless a line comment
+        // has to end one of the lines
+        let numbers = elements.len() > 1
+            && elements.iter().flatten().all(is_number)
+            && !printed.iter().any(Doc::forces_break);
+        if !numbers {
+            return self.print_list("[", "]", printed, trailing_comma, end);
+        }
+
+        let count = printed.len();
+        let mut parts = Vec::new();
+        for (index, element) in printed.into_iter().enumerate() {
+            if index + 1 < count {
+                parts.push(Doc::concat(vec![element, Doc::text(",")]));
+                parts.push(Doc::line());
+            } else {
+                parts.push(element);
+            }
+        }
+        let trailing_comma = if trailing_comma {
+            Doc::if_break(Doc::text(","), Doc::nil())
+        } else {
+            Doc::nil()
+        };
+        Doc::group(Doc::concat(vec![
+            Doc::text("["),
+            Doc::indent(Doc::concat(vec![
+                Doc::softline(),
+                Doc::fill(parts),
+                trailing_comma,
+            ])),
+            Doc::softline(),
+            Doc::text("]"),
+        ]))
+    }
+
+    /// Print a conditional expression, which breaks before its `?` and `:` with the
+    /// branches indented. Nested conditionals are part of the same chain and break
+    /// with it, each level indented further.
+    fn print_conditional(
+        &mut self,
+        test: &Expression,
+        consequent: &Expression,
+        alternate: &Expression,
+    ) -> Doc {
+        // Comments before the `?` and `:` trail the test and the consequent
+        let question = self.code_after(test.span.end);
+        let colon = self.code_after(consequent.span.end);
+        Doc::concat(vec![
+            self.print_expression(test),
+            self.print_remaining_comments(question),
+            Doc::indent(Doc::concat(vec![
+                Doc::line(),
+                Doc::text("? "),
+                self.print_conditional_branch(consequent),
+                self.print_remaining_comments(colon),
+                Doc::line(),
+                Doc::text(": "),
+                self.print_conditional_branch(alternate),
+            ])),
+        ])
+    }
+
+    fn print_conditional_branch(&mut self, branch: &Expression) -> Doc {
+        let ExpressionKind::Conditional {
+            test,
+            consequent,
+            alternate,
+        } = &branch.kind
+        else {
+            return self.print_expression(branch);
+        };
+        let mut parts = self.print_inline_comments(branch.span.start);
+        parts.push(self.print_conditional(test, consequent, alternate));
+        Doc::concat(parts)
+    }
+
+    fn print_arrow(&mut self, arrow: &ArrowFunction) -> Doc {
+        let mut parts = Vec::new();
+        if arrow.is_async {
+            parts.push(Doc::text("async "));
+        }
+        if arrow.parenthesized {
+            parts.push(self.print_params(&arrow.params, arrow.params_end));
+        } else {
+            let params = self.print_each(&arrow.params);
+            parts.push(Doc::join(Doc::text(", "), params));
+        }
+        parts.push(Doc::text(" =>"));
+
+        match &arrow.body {
+            ArrowBody::Block(block) => parts.push(self.print_header_block(block)),
+            ArrowBody::Expression(body) => {
+                // Bodies that break on their own stay on the arrow's line
+                let same_line = match &body.kind {
+                    ExpressionKind::Object(_)
+                    | ExpressionKind::Array(_)
+                    | ExpressionKind::ArrowFunction(_) => true,
+                    ExpressionKind::Parenthesized(inner) => {
+                        matches!(inner.kind, ExpressionKind::Object(_))
+                    }
+                    _ => false,
+                };
+                let body = self.print_expression(body);
+                if same_line {
+                    parts.push(Doc::text(" "));
+                    parts.push(body);
+                } else {
+                    parts.push(Doc::group(Doc::indent(Doc::concat(vec![
+                        Doc::line(),
+                        body,
+                    ]))));
+                }
+            }
+        }
+        Doc::concat(parts)
+    }
+
+    fn print_property(&mut self, property: &Property) -> Doc {
+        match &property.kind {
+            PropertyKind::KeyValue { key, value } => {
+                let key = self.print_property_key(key);
+                self.print_assignment(key, ":", value)
+            }
+            PropertyKind::Shorthand(value) => self.print_expression(value),
+            PropertyKind::Method {
+                kind,
+                key,
+                function,
+            } => self.print_method(*kind, key, function),
+            PropertyKind::Spread(argument) => {
+                Doc::concat(vec![Doc::text("..."), self.print_expression(argument)])
+            }
+        }
+    }
+}
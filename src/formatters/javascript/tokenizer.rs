@@ -1,6 +1,54 @@
 //! JavaScript tokenizer implementation
 
-use crate::core::tokens::{Token, Tokenizer};
+use crate::core::error::{LexError, LexErrorKind, Location};
+use crate::core::tokens::{
+    is_line_terminator, CommentKind, Span, TemplateKind, Token, TokenKind, Tokenizer,
+};
+
+/// JavaScript keywords
+const KEYWORDS: [&str; 41] = [
+    "var",
+    "let",
+    "const",
+    "function",
+    "return",
+    "if",
+    "else",
+    "for",
+    "while",
+    "with",
+    "do",
+    "switch",
+    "case",
+    "default",
+    "break",
+    "continue",
+    "try",
+    "catch",
+    "finally",
+    "throw",
+    "new",
+    "delete",
+    "typeof",
+    "instanceof",
+    "in",
+    "this",
+    "super",
+    "class",
+    "extends",
+    "import",
+    "export",
+    "from",
+    "as",
+    "async",
+    "await",
+    "yield",
+    "true",
+    "false",
+    "null",
+    "undefined",
+    "void",
+];
 
 /// JavaScript tokenizer implementation
 pub struct JavaScriptTokenizer;
@@ -13,338 +61,721 @@ impl JavaScriptTokenizer {
 }
 
 impl Tokenizer for JavaScriptTokenizer {
-    fn tokenize(&self, content: &str) -> Vec<Token> {
+    fn tokenize(&self, content: &str) -> Result<Vec<Token>, LexError> {
         tokenize_javascript(content)
     }
 }
 
 /// Tokenize JavaScript code
-pub(crate) fn tokenize_javascript(content: &str) -> Vec<Token> {
-    let mut tokens = Vec::new();
-    let mut chars = content.chars().peekable();
-
-    let mut in_string = false;
-    let mut string_delimiter = '"';
-    let mut current_string = String::new();
-
-    let mut in_comment = false;
-    let mut in_multiline_comment = false;
-    let mut current_comment = String::new();
-
-    let mut in_identifier = false;
-    let mut current_identifier = String::new();
-
-    let mut in_number = false;
-    let mut current_number = String::new();
-
-    let mut in_operator = false;
-    let mut current_operator = String::new();
-
-    let keywords = [
-        "var",
-        "let",
-        "const",
-        "function",
-        "return",
-        "if",
-        "else",
-        "for",
-        "while",
-        "do",
-        "switch",
-        "case",
-        "default",
-        "break",
-        "continue",
-        "try",
-        "catch",
-        "finally",
-        "throw",
-        "new",
-        "delete",
-        "typeof",
-        "instanceof",
-        "in",
-        "this",
-        "super",
-        "class",
-        "extends",
-        "import",
-        "export",
-        "from",
-        "as",
-        "async",
-        "await",
-        "yield",
-        "true",
-        "false",
-        "null",
-        "undefined",
-        "void",
-    ];
-
-    while let Some(c) = chars.next() {
-        // Handle string literals
-        if in_string {
-            if c == string_delimiter && chars.peek() != Some(&'\\') {
-                in_string = false;
-                tokens.push(Token::StringLiteral(current_string.clone()));
-                current_string.clear();
+pub(crate) fn tokenize_javascript(content: &str) -> Result<Vec<Token>, LexError> {
+    Lexer::new(content).tokenize()
+}
+
+/// Single-pass lexer over JavaScript source
+struct Lexer<'a> {
+    content: &'a str,
+    /// Byte offset of the next character
+    pos: usize,
+    tokens: Vec<Token>,
+    /// Line and column bookkeeping for token spans
+    lines: LineTracker,
+    /// Open parentheses and braces
+    groups: Vec<Group>,
+    /// The group closed by the last `)` or `}`
+    closed: Option<Group>,
+    /// Whether the function whose parameters come next is an expression, set by the
+    /// `function` keyword
+    function_expression: Option<bool>,
+}
+
+/// What an open `(` or `{` belongs to, which decides whether a `/` after the
+/// matching close starts a regular expression or divides
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Group {
+    /// The header of `if`, `while`, `for` or `with`, which a statement follows
+    Header,
+    /// The parameters of a function, which its body follows
+    Params { expression: bool },
+    /// Any other parentheses, which end an expression
+    Parens,
+    /// A block statement or the body of a function declaration or arrow function,
+    /// which a statement follows
+    Block,
+    /// The body of a function expression, which ends an expression
+    FunctionBody,
+    /// An object literal or class body, which ends an expression
+    Object,
+    /// A template substitution, which the rest of the template follows
+    Template,
+}
+
+/// Incrementally maps increasing byte offsets to lines and columns
+struct LineTracker {
+    offset: usize,
+    line: usize,
+    column: usize,
+}
+
+impl LineTracker {
+    /// Advance to `offset`, which must not be before the last offset
+    fn advance(&mut self, content: &str, offset: usize) -> (usize, usize) {
+        for (index, c) in content[self.offset..offset].char_indices() {
+            if is_line_terminator(c) {
+                // `\r\n` is a single line break
+                if !(c == '\n' && content[..self.offset + index].ends_with('\r')) {
+                    self.line += 1;
+                }
+                self.column = 1;
             } else {
-                current_string.push(c);
+                self.column += 1;
             }
-            continue;
         }
+        self.offset = offset;
+        (self.line, self.column)
+    }
+}
 
-        // Handle comments
-        if in_comment {
-            if c == '\n' {
-                in_comment = false;
-                tokens.push(Token::Comment(current_comment.clone()));
-                tokens.push(Token::Newline);
-                current_comment.clear();
-            } else {
-                current_comment.push(c);
-            }
-            continue;
+impl<'a> Lexer<'a> {
+    fn new(content: &'a str) -> Self {
+        Self {
+            content,
+            pos: 0,
+            tokens: Vec::new(),
+            lines: LineTracker {
+                offset: 0,
+                line: 1,
+                column: 1,
+            },
+            groups: Vec::new(),
+            closed: None,
+            function_expression: None,
         }
+    }
 
-        if in_multiline_comment {
-            if c == '*' && chars.peek() == Some(&'/') {
-                chars.next(); // Consume the '/'
-                in_multiline_comment = false;
-                tokens.push(Token::Comment(current_comment.clone()));
-                current_comment.clear();
-            } else {
-                current_comment.push(c);
-                if c == '\n' {
-                    tokens.push(Token::Newline);
+    /// Push a token spanning from `start` to the current position
+    fn push(&mut self, kind: TokenKind, start: usize) {
+        let (line, column) = self.lines.advance(self.content, start);
+        let span = Span {
+            start,
+            end: self.pos,
+            line,
+            column,
+        };
+        let text = &self.content[start..self.pos];
+        self.tokens.push(Token::new(kind, span, text));
+    }
+
+    /// Peek at the next character without consuming it
+    fn peek(&self) -> Option<char> {
+        self.content[self.pos..].chars().next()
+    }
+
+    /// Peek at the character after the next one
+    fn peek_second(&self) -> Option<char> {
+        self.content[self.pos..].chars().nth(1)
+    }
+
+    /// Consume the next character
+    fn bump(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.pos += c.len_utf8();
+        Some(c)
+    }
+
+    /// Consume characters while `predicate` holds
+    fn bump_while(&mut self, predicate: impl Fn(char) -> bool) {
+        while self.peek().is_some_and(&predicate) {
+            self.bump();
+        }
+    }
+
+    /// Build an error at a byte offset
+    fn error(&self, kind: LexErrorKind, offset: usize) -> LexError {
+        LexError {
+            kind,
+            location: Location::from_offset(self.content, offset),
+        }
+    }
+
+    fn tokenize(mut self) -> Result<Vec<Token>, LexError> {
+        while let Some(c) = self.peek() {
+            let start = self.pos;
+            match c {
+                '{' => {
+                    let group = self.brace_group();
+                    self.groups.push(group);
+                    self.single(TokenKind::OpenBrace);
+                }
+                '}' if self.groups.last() == Some(&Group::Template) => {
+                    self.groups.pop();
+                    self.lex_template(start)?
+                }
+                '}' => {
+                    self.closed = self.groups.pop();
+                    self.single(TokenKind::CloseBrace);
+                }
+                '`' => self.lex_template(start)?,
+                '(' => {
+                    let group = self.paren_group();
+                    self.groups.push(group);
+                    self.single(TokenKind::OpenParen);
+                }
+                ')' => {
+                    self.closed = self.groups.pop();
+                    self.single(TokenKind::CloseParen);
+                }
+                '[' => self.single(TokenKind::OpenBracket),
+                ']' => self.single(TokenKind::CloseBracket),
+                ';' => self.single(TokenKind::Semicolon),
+                ':' => self.single(TokenKind::Colon),
+                ',' => self.single(TokenKind::Comma),
+                '.' if self.peek_second().is_some_and(|next| next.is_ascii_digit()) => {
+                    self.lex_number(start)?
+                }
+                '.' if self.content[self.pos..].starts_with("...") => {
+                    self.pos += 3;
+                    self.push(TokenKind::Ellipsis, start);
                 }
+                '.' => self.single(TokenKind::Dot),
+                // `a?.5:1` is a conditional, not optional chaining
+                '?' if self.content[self.pos..].starts_with("?.")
+                    && !self.content[self.pos + 2..].starts_with(|c: char| c.is_ascii_digit()) =>
+                {
+                    self.pos += 2;
+                    self.push(TokenKind::QuestionDot, start);
+                }
+                '#' if self.peek_second() == Some('!')
+                    && self.content[..start]
+                        .trim_start_matches('\u{feff}')
+                        .is_empty() =>
+                {
+                    self.lex_hashbang(start)
+                }
+                '#' if self.content[self.pos + 1..]
+                    .starts_with(|c: char| is_id_start(c) || c == '\\') =>
+                {
+                    self.lex_private_name(start)?
+                }
+                '"' | '\'' => self.lex_string(c, start)?,
+                '/' if self.peek_second() == Some('/') => self.lex_line_comment(start),
+                '/' if self.peek_second() == Some('*') => self.lex_block_comment(start)?,
+                '/' if self.regex_allowed() => self.lex_regex(start)?,
+                '0'..='9' => self.lex_number(start)?,
+                '\\' if self.peek_second() == Some('u') => self.lex_identifier(start)?,
+                _ if is_id_start(c) => self.lex_identifier(start)?,
+                _ if is_line_terminator(c) => {
+                    self.bump();
+                    if c == '\r' && self.peek() == Some('\n') {
+                        self.bump();
+                    }
+                    self.push(TokenKind::Newline, start);
+                }
+                _ if is_whitespace(c) => {
+                    self.bump_while(is_whitespace);
+                    let whitespace = self.content[start..self.pos].to_string();
+                    self.push(TokenKind::Whitespace(whitespace), start);
+                }
+                _ if is_operator_char(c) => self.lex_operator(start),
+                _ if is_invalid_char(c) => {
+                    return Err(self.error(LexErrorKind::InvalidCharacter(c), start));
+                }
+                _ => self.single(TokenKind::Other(c)),
             }
-            continue;
         }
 
-        // Handle identifiers
-        if in_identifier {
-            if c.is_alphanumeric() || c == '_' || c == '$' {
-                current_identifier.push(c);
-            } else {
-                in_identifier = false;
-                if keywords.contains(&current_identifier.as_str()) {
-                    tokens.push(Token::Keyword(current_identifier.clone()));
-                } else {
-                    tokens.push(Token::Identifier(current_identifier.clone()));
+        Ok(self.tokens)
+    }
+
+    /// Consume one character and push a token of `kind`
+    fn single(&mut self, kind: TokenKind) {
+        let start = self.pos;
+        self.bump();
+        self.push(kind, start);
+    }
+
+    /// Lex a string literal delimited by `quote`
+    fn lex_string(&mut self, quote: char, start: usize) -> Result<(), LexError> {
+        self.bump(); // Consume the opening quote
+        let content_start = self.pos;
+
+        loop {
+            match self.bump() {
+                Some(c) if c == quote => break,
+                Some('\\') => {
+                    // Skip the escaped character, including escaped line breaks
+                    match self.bump() {
+                        Some('\r') if self.peek() == Some('\n') => {
+                            self.bump();
+                        }
+                        Some(_) => {}
+                        None => {
+                            return Err(self.error(LexErrorKind::UnterminatedString, start));
+                        }
+                    }
                 }
-                current_identifier.clear();
-
-                // Process the current character
-                process_char(
-                    c,
-                    &mut chars,
-                    &mut tokens,
-                    &mut in_string,
-                    &mut string_delimiter,
-                    &mut current_string,
-                    &mut in_comment,
-                    &mut in_multiline_comment,
-                    &mut current_comment,
-                    &mut in_number,
-                    &mut current_number,
-                    &mut in_operator,
-                    &mut current_operator,
-                    &mut in_identifier,
-                    &mut current_identifier,
-                );
+                // Unlike LINE SEPARATOR and PARAGRAPH SEPARATOR, a line feed or carriage
+                // return cannot appear unescaped in a string
+                Some('\n' | '\r') | None => {
+                    return Err(self.error(LexErrorKind::UnterminatedString, start));
+                }
+                Some(_) => {}
             }
-            continue;
         }
 
-        // Handle numbers
-        if in_number {
-            if c.is_digit(10) || c == '.' || c == 'e' || c == 'E' || c == '+' || c == '-' {
-                current_number.push(c);
-            } else {
-                in_number = false;
-                tokens.push(Token::NumberLiteral(current_number.clone()));
-                current_number.clear();
-
-                // Process the current character
-                process_char(
-                    c,
-                    &mut chars,
-                    &mut tokens,
-                    &mut in_string,
-                    &mut string_delimiter,
-                    &mut current_string,
-                    &mut in_comment,
-                    &mut in_multiline_comment,
-                    &mut current_comment,
-                    &mut in_number,
-                    &mut current_number,
-                    &mut in_operator,
-                    &mut current_operator,
-                    &mut in_identifier,
-                    &mut current_identifier,
-                );
+        let value = &self.content[content_start..self.pos - quote.len_utf8()];
+        self.push(TokenKind::StringLiteral(value.to_string()), start);
+        Ok(())
+    }
+
+    /// Lex a piece of a template literal, starting at its opening `` ` `` or at the
+    /// `}` closing a substitution
+    fn lex_template(&mut self, start: usize) -> Result<(), LexError> {
+        let continued = self.bump() == Some('}');
+        let content_start = self.pos;
+
+        let (kind, content_end) = loop {
+            match self.bump() {
+                Some('`') => {
+                    let kind = if continued {
+                        TemplateKind::Tail
+                    } else {
+                        TemplateKind::NoSubstitution
+                    };
+                    break (kind, self.pos - 1);
+                }
+                Some('$') if self.peek() == Some('{') => {
+                    self.bump();
+                    self.groups.push(Group::Template);
+                    let kind = if continued {
+                        TemplateKind::Middle
+                    } else {
+                        TemplateKind::Head
+                    };
+                    break (kind, self.pos - 2);
+                }
+                Some('\\') => {
+                    if self.bump().is_none() {
+                        return Err(self.error(LexErrorKind::UnterminatedTemplate, start));
+                    }
+                }
+                Some(_) => {}
+                None => return Err(self.error(LexErrorKind::UnterminatedTemplate, start)),
+            }
+        };
+
+        let raw = self.content[content_start..content_end].to_string();
+        self.push(TokenKind::Template(kind, raw), start);
+        Ok(())
+    }
+
+    /// The `n`th last significant token, counting from 0
+    fn previous_token(&self, n: usize) -> Option<&Token> {
+        self.tokens
+            .iter()
+            .rev()
+            .filter(|token| !token.kind.is_trivia())
+            .nth(n)
+    }
+
+    /// Check if a statement can start after the last significant token
+    fn statement_allowed(&self, previous: Option<&Token>) -> bool {
+        let Some(previous) = previous else {
+            return true;
+        };
+        match &previous.kind {
+            TokenKind::Semicolon => true,
+            TokenKind::CloseParen => self.closed == Some(Group::Header),
+            TokenKind::CloseBrace => self.closed == Some(Group::Block),
+            // Labels and `case` clauses in a block
+            TokenKind::OpenBrace | TokenKind::Colon => {
+                matches!(
+                    self.groups.last(),
+                    None | Some(Group::Block | Group::FunctionBody)
+                )
+            }
+            TokenKind::Keyword(keyword) => {
+                matches!(keyword.as_str(), "else" | "do" | "export" | "default")
             }
-            continue;
+            _ => false,
         }
+    }
 
-        // Handle operators
-        if in_operator {
-            if is_operator_char(c) {
-                current_operator.push(c);
-            } else {
-                in_operator = false;
-                tokens.push(Token::Operator(current_operator.clone()));
-                current_operator.clear();
-
-                // Process the current character
-                process_char(
-                    c,
-                    &mut chars,
-                    &mut tokens,
-                    &mut in_string,
-                    &mut string_delimiter,
-                    &mut current_string,
-                    &mut in_comment,
-                    &mut in_multiline_comment,
-                    &mut current_comment,
-                    &mut in_number,
-                    &mut current_number,
-                    &mut in_operator,
-                    &mut current_operator,
-                    &mut in_identifier,
-                    &mut current_identifier,
-                );
+    /// The group a `(` at the current position opens
+    fn paren_group(&mut self) -> Group {
+        if let Some(expression) = self.function_expression.take() {
+            return Group::Params { expression };
+        }
+        let is_header = |token: Option<&Token>| {
+            matches!(token.map(|token| &token.kind), Some(TokenKind::Keyword(keyword))
+                if matches!(keyword.as_str(), "if" | "while" | "for" | "with"))
+        };
+        let previous = self.previous_token(0);
+        // `for await (`
+        let is_for_await = matches!(previous.map(|token| &token.kind),
+            Some(TokenKind::Keyword(keyword)) if keyword == "await")
+            && is_header(self.previous_token(1));
+        if is_header(previous) || is_for_await {
+            Group::Header
+        } else {
+            Group::Parens
+        }
+    }
+
+    /// The group a `{` at the current position opens
+    fn brace_group(&self) -> Group {
+        let previous = self.previous_token(0);
+        match previous.map(|token| &token.kind) {
+            Some(TokenKind::CloseParen) => match self.closed {
+                Some(Group::Params { expression: true }) => Group::FunctionBody,
+                // Control statement and function bodies, `catch (e) {` and methods
+                _ => Group::Block,
+            },
+            Some(TokenKind::Operator(operator)) if operator == "=>" => Group::Block,
+            Some(TokenKind::Keyword(keyword)) if matches!(keyword.as_str(), "try" | "finally") => {
+                Group::Block
             }
-            continue;
+            _ if self.statement_allowed(previous) => Group::Block,
+            _ => Group::Object,
         }
+    }
 
-        // Start of new tokens
-        process_char(
-            c,
-            &mut chars,
-            &mut tokens,
-            &mut in_string,
-            &mut string_delimiter,
-            &mut current_string,
-            &mut in_comment,
-            &mut in_multiline_comment,
-            &mut current_comment,
-            &mut in_number,
-            &mut current_number,
-            &mut in_operator,
-            &mut current_operator,
-            &mut in_identifier,
-            &mut current_identifier,
-        );
+    /// Check if a `/` at the current position starts a regular expression rather than
+    /// a division, from the last significant token: a regex can only appear where an
+    /// expression is expected
+    fn regex_allowed(&self) -> bool {
+        let Some(previous) = self.previous_token(0) else {
+            return true;
+        };
+
+        match &previous.kind {
+            // A statement follows the header of a control statement and a block
+            TokenKind::CloseParen => self.closed == Some(Group::Header),
+            TokenKind::CloseBrace => self.closed == Some(Group::Block),
+            // `of` after the binding of a `for` header is followed by an expression
+            TokenKind::Identifier(name) if name == "of" => {
+                self.groups.last() == Some(&Group::Header)
+                    && matches!(
+                        self.previous_token(1).map(|token| &token.kind),
+                        Some(
+                            TokenKind::Identifier(_)
+                                | TokenKind::CloseBracket
+                                | TokenKind::CloseBrace
+                        )
+                    )
+            }
+            // These end an operand, so the `/` divides it
+            TokenKind::Identifier(_)
+            | TokenKind::PrivateName(_)
+            | TokenKind::NumberLiteral(_)
+            | TokenKind::StringLiteral(_)
+            | TokenKind::RegexLiteral(_)
+            | TokenKind::Template(TemplateKind::NoSubstitution | TemplateKind::Tail, _)
+            | TokenKind::CloseBracket => false,
+            TokenKind::Keyword(keyword) => !matches!(
+                keyword.as_str(),
+                "this"
+                    | "super"
+                    | "true"
+                    | "false"
+                    | "null"
+                    | "undefined"
+                    | "from"
+                    | "as"
+                    | "async"
+                    | "let"
+            ),
+            TokenKind::Operator(operator) => operator != "++" && operator != "--",
+            _ => true,
+        }
     }
 
-    // Handle any remaining tokens
-    if in_identifier {
-        if keywords.contains(&current_identifier.as_str()) {
-            tokens.push(Token::Keyword(current_identifier));
-        } else {
-            tokens.push(Token::Identifier(current_identifier));
+    /// Lex a regular expression literal and its flags
+    fn lex_regex(&mut self, start: usize) -> Result<(), LexError> {
+        self.bump(); // Consume the opening `/`
+        let mut in_class = false;
+
+        loop {
+            match self.bump() {
+                Some('\\') => {
+                    if self.bump().is_none_or(is_line_terminator) {
+                        return Err(self.error(LexErrorKind::UnterminatedRegex, start));
+                    }
+                }
+                Some('[') => in_class = true,
+                Some(']') => in_class = false,
+                Some('/') if !in_class => break,
+                Some(c) if is_line_terminator(c) => {
+                    return Err(self.error(LexErrorKind::UnterminatedRegex, start));
+                }
+                None => {
+                    return Err(self.error(LexErrorKind::UnterminatedRegex, start));
+                }
+                Some(_) => {}
+            }
         }
+        self.bump_while(is_id_continue);
+
+        let regex = self.content[start..self.pos].to_string();
+        self.push(TokenKind::RegexLiteral(regex), start);
+        Ok(())
     }
 
-    if in_number {
-        tokens.push(Token::NumberLiteral(current_number));
+    /// Lex a `//` comment up to the end of the line
+    fn lex_line_comment(&mut self, start: usize) {
+        self.pos += 2; // Consume the `//`
+        let content_start = self.pos;
+        self.bump_while(|c| !is_line_terminator(c));
+
+        let text = &self.content[content_start..self.pos];
+        self.push(
+            TokenKind::Comment(CommentKind::Line, text.to_string()),
+            start,
+        );
     }
 
-    if in_operator {
-        tokens.push(Token::Operator(current_operator));
+    /// Lex a `#!` line at the start of the file
+    fn lex_hashbang(&mut self, start: usize) {
+        self.pos += 2; // Consume the `#!`
+        let content_start = self.pos;
+        self.bump_while(|c| !is_line_terminator(c));
+
+        let text = &self.content[content_start..self.pos];
+        self.push(
+            TokenKind::Comment(CommentKind::Hashbang, text.to_string()),
+            start,
+        );
     }
 
-    tokens
-}
+    /// Lex a `/* */` comment
+    fn lex_block_comment(&mut self, start: usize) -> Result<(), LexError> {
+        self.pos += 2; // Consume the `/*`
+        let content_start = self.pos;
 
-/// Process a single character during tokenization
-#[allow(clippy::too_many_arguments)]
-fn process_char(
-    c: char,
-    chars: &mut std::iter::Peekable<std::str::Chars>,
-    tokens: &mut Vec<Token>,
-    in_string: &mut bool,
-    string_delimiter: &mut char,
-    _current_string: &mut String,
-    in_comment: &mut bool,
-    in_multiline_comment: &mut bool,
-    _current_comment: &mut String,
-    in_number: &mut bool,
-    current_number: &mut String,
-    in_operator: &mut bool,
-    current_operator: &mut String,
-    in_identifier: &mut bool,
-    current_identifier: &mut String,
-) {
-    match c {
-        '{' => tokens.push(Token::OpenBrace),
-        '}' => tokens.push(Token::CloseBrace),
-        '(' => tokens.push(Token::OpenParen),
-        ')' => tokens.push(Token::CloseParen),
-        '[' => tokens.push(Token::OpenBracket),
-        ']' => tokens.push(Token::CloseBracket),
-        ';' => tokens.push(Token::Semicolon),
-        ':' => tokens.push(Token::Colon),
-        ',' => tokens.push(Token::Comma),
-        '.' => {
-            if chars.peek().map_or(false, |&next| next.is_digit(10)) {
-                *in_number = true;
-                current_number.push(c);
-            } else {
-                tokens.push(Token::Dot);
+        let Some(length) = self.content[content_start..].find("*/") else {
+            return Err(self.error(LexErrorKind::UnterminatedBlockComment, start));
+        };
+        let text = &self.content[content_start..content_start + length];
+        self.pos = content_start + length + 2;
+
+        self.push(
+            TokenKind::Comment(CommentKind::Block, text.to_string()),
+            start,
+        );
+        Ok(())
+    }
+
+    /// Lex a number literal
+    fn lex_number(&mut self, start: usize) -> Result<(), LexError> {
+        let radix = match (self.peek(), self.peek_second()) {
+            (Some('0'), Some('x' | 'X')) => Some(16),
+            (Some('0'), Some('o' | 'O')) => Some(8),
+            (Some('0'), Some('b' | 'B')) => Some(2),
+            _ => None,
+        };
+
+        if let Some(radix) = radix {
+            self.bump();
+            self.bump();
+            if !self.lex_digits(|c| c.is_digit(radix)) {
+                return Err(self.error(LexErrorKind::InvalidNumber, start));
+            }
+            if self.peek() == Some('n') {
+                self.bump();
+            }
+        } else {
+            let integer = self.lex_digits(|c| c.is_ascii_digit());
+            let fraction = self.peek() == Some('.');
+            if fraction {
+                self.bump();
+                self.lex_digits(|c| c.is_ascii_digit());
+            }
+            if matches!(self.peek(), Some('e' | 'E')) {
+                self.bump();
+                if matches!(self.peek(), Some('+' | '-')) {
+                    self.bump();
+                }
+                if !self.lex_digits(|c| c.is_ascii_digit()) {
+                    return Err(self.error(LexErrorKind::InvalidNumber, start));
+                }
+            } else if integer && !fraction && self.peek() == Some('n') {
+                self.bump();
             }
         }
-        '"' | '\'' => {
-            *in_string = true;
-            *string_delimiter = c;
+
+        // A number must not run into an identifier, as in `3in` or `0x1g`
+        if self.peek().is_some_and(|c| is_id_continue(c) || c == '\\') {
+            return Err(self.error(LexErrorKind::InvalidNumber, start));
         }
-        '/' => {
-            if chars.peek() == Some(&'/') {
-                chars.next(); // Consume the second '/'
-                *in_comment = true;
-            } else if chars.peek() == Some(&'*') {
-                chars.next(); // Consume the '*'
-                *in_multiline_comment = true;
-            } else if is_operator_char(c) {
-                *in_operator = true;
-                current_operator.push(c);
-            } else {
-                tokens.push(Token::Other(c));
+
+        let number = self.content[start..self.pos].to_string();
+        self.push(TokenKind::NumberLiteral(number), start);
+        Ok(())
+    }
+
+    /// Lex a run of digits with `_` separators between them, returning whether there
+    /// were any digits
+    fn lex_digits(&mut self, is_digit: impl Fn(char) -> bool) -> bool {
+        let start = self.pos;
+        while let Some(c) = self.peek() {
+            let separator =
+                c == '_' && self.pos > start && self.peek_second().is_some_and(&is_digit);
+            if !is_digit(c) && !separator {
+                break;
             }
+            self.bump();
         }
-        '0'..='9' => {
-            *in_number = true;
-            current_number.push(c);
-        }
-        'a'..='z' | 'A'..='Z' | '_' | '$' => {
-            *in_identifier = true;
-            current_identifier.push(c);
+        self.pos > start
+    }
+
+    /// Lex an identifier or keyword
+    fn lex_identifier(&mut self, start: usize) -> Result<(), LexError> {
+        let escaped = self.lex_identifier_name()?;
+
+        // An escaped keyword, or a keyword naming a property as in `a.new`, is an
+        // ordinary identifier
+        let word = &self.content[start..self.pos];
+        let is_property = matches!(
+            self.previous_token(0).map(|token| &token.kind),
+            Some(TokenKind::Dot | TokenKind::QuestionDot)
+        );
+        if !escaped && !is_property && KEYWORDS.contains(&word) {
+            if word == "function" {
+                // A function is a declaration where a statement can start
+                let mut previous = self.previous_token(0);
+                if matches!(previous.map(|token| &token.kind),
+                    Some(TokenKind::Keyword(keyword)) if keyword == "async")
+                {
+                    previous = self.previous_token(1);
+                }
+                self.function_expression = Some(!self.statement_allowed(previous));
+            }
+            self.push(TokenKind::Keyword(word.to_string()), start);
+        } else {
+            self.push(TokenKind::Identifier(word.to_string()), start);
         }
-        ' ' | '\t' => {
-            tokens.push(Token::Whitespace(c.to_string()));
+        Ok(())
+    }
+
+    /// Consume an identifier name, which may contain `\\u` escapes, returning whether
+    /// it did
+    fn lex_identifier_name(&mut self) -> Result<bool, LexError> {
+        let start = self.pos;
+        let mut escaped = false;
+        loop {
+            let char_start = self.pos;
+            match self.peek() {
+                Some('\\') => {
+                    escaped = true;
+                    let valid = match self.lex_unicode_escape() {
+                        Some(c) if char_start == start => is_id_start(c),
+                        Some(c) => is_id_continue(c),
+                        None => false,
+                    };
+                    if !valid {
+                        return Err(self.error(LexErrorKind::InvalidEscape, char_start));
+                    }
+                }
+                Some(c) if is_id_continue(c) => {
+                    self.bump();
+                }
+                _ => break,
+            }
         }
-        '\n' => {
-            tokens.push(Token::Newline);
+        Ok(escaped)
+    }
+
+    /// Lex a `\\uXXXX` or `\\u{X}` escape, returning the character it encodes
+    fn lex_unicode_escape(&mut self) -> Option<char> {
+        self.bump(); // Consume the `\\`
+        if self.bump() != Some('u') {
+            return None;
         }
-        _ => {
-            if is_operator_char(c) {
-                *in_operator = true;
-                current_operator.push(c);
-            } else {
-                tokens.push(Token::Other(c));
+
+        let digits_start = self.pos;
+        let digits = if self.peek() == Some('{') {
+            self.bump();
+            self.bump_while(|c| c.is_ascii_hexdigit());
+            let digits = &self.content[digits_start + 1..self.pos];
+            if self.bump() != Some('}') {
+                return None;
             }
-        }
+            digits
+        } else {
+            for _ in 0..4 {
+                if !self.bump().is_some_and(|c| c.is_ascii_hexdigit()) {
+                    return None;
+                }
+            }
+            &self.content[digits_start..self.pos]
+        };
+
+        u32::from_str_radix(digits, 16)
+            .ok()
+            .and_then(char::from_u32)
+    }
+
+    /// Lex the longest punctuator at the current position. `/` and `/=` only get
+    /// here where they divide, since a regular expression is lexed before.
+    fn lex_operator(&mut self, start: usize) {
+        let rest = &self.content[self.pos..];
+        let operator = PUNCTUATORS
+            .iter()
+            .find(|punctuator| rest.starts_with(*punctuator))
+            .copied()
+            .unwrap_or(&rest[..1]);
+
+        self.pos += operator.len();
+        self.push(TokenKind::Operator(operator.to_string()), start);
+    }
+
+    /// Lex a `#name` private class member name
+    fn lex_private_name(&mut self, start: usize) -> Result<(), LexError> {
+        self.bump(); // Consume the `#`
+        self.lex_identifier_name()?;
+
+        let name = self.content[start..self.pos].to_string();
+        self.push(TokenKind::PrivateName(name), start);
+        Ok(())
     }
 }
 
-/// Check if a character is part of an operator
+/// Operator punctuators, longest first so the first match is the longest
+const PUNCTUATORS: [&str; 45] = [
+    ">>>=", "===", "!==", "**=", "<<=", ">>=", ">>>", "&&=", "||=", "??=", "=>", "==", "!=", "<=",
+    ">=", "&&", "||", "??", "++", "--", "+=", "-=", "*=", "/=", "%=", "&=", "|=", "^=", "**", "<<",
+    ">>", "+", "-", "*", "/", "%", "=", "!", "<", ">", "&", "|", "^", "~", "?",
+];
+
+/// Check if a character starts an operator
 fn is_operator_char(c: char) -> bool {
     matches!(
         c,
         '+' | '-' | '*' | '/' | '%' | '=' | '!' | '<' | '>' | '&' | '|' | '^' | '~' | '?'
     )
 }
+
+/// Check if a character can start an identifier
+fn is_id_start(c: char) -> bool {
+    c == '$' || c == '_' || unicode_ident::is_xid_start(c)
+}
+
+/// Check if a character can continue an identifier, including the zero-width joiners
+fn is_id_continue(c: char) -> bool {
+    c == '$' || c == '\u{200c}' || c == '\u{200d}' || unicode_ident::is_xid_continue(c)
+}
+
+/// Check if a character is whitespace other than a line terminator: tab, vertical
+/// tab, form feed, the byte order mark and the Unicode space separators
+fn is_whitespace(c: char) -> bool {
+    match c {
+        '\t' | '\u{b}' | '\u{c}' | ' ' | '\u{feff}' => true,
+        // Outside of ASCII, Rust's whitespace is the space separators plus NEL and
+        // the line terminators
+        _ => !c.is_ascii() && c.is_whitespace() && c != '\u{85}' && !is_line_terminator(c),
+    }
+}
+
+/// Check if a character can never appear outside of strings and comments
+fn is_invalid_char(c: char) -> bool {
+    c.is_control() || !c.is_ascii()
+}
@@ -0,0 +1,47 @@
+//! Languages supported by the built-in formatters
+
+use crate::core::formatter::Formatter;
+use crate::core::tokens::Tokenizer;
+use crate::formatters::javascript::{JavaScriptFormatter, JavaScriptTokenizer};
+use std::path::Path;
+
+/// A language with a built-in formatter
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Language {
+    /// JavaScript (`.js`, `.mjs`, `.cjs`)
+    JavaScript,
+}
+
+impl Language {
+    /// All languages with a built-in formatter
+    pub const ALL: [Language; 1] = [Language::JavaScript];
+
+    /// Get the language for a file extension, without the leading dot
+    pub fn from_extension(extension: &str) -> Option<Language> {
+        match extension.to_lowercase().as_str() {
+            "js" | "mjs" | "cjs" => Some(Language::JavaScript),
+            _ => None,
+        }
+    }
+
+    /// Get the language of a file from its extension
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Option<Language> {
+        path.as_ref()
+            .extension()
+            .and_then(|extension| Language::from_extension(&extension.to_string_lossy()))
+    }
+
+    /// Create the formatter for this language
+    pub(crate) fn formatter(self) -> Box<dyn Formatter> {
+        match self {
+            Language::JavaScript => Box::new(JavaScriptFormatter::new()),
+        }
+    }
+
+    /// Create the tokenizer for this language
+    pub(crate) fn tokenizer(self) -> Box<dyn Tokenizer> {
+        match self {
+            Language::JavaScript => Box::new(JavaScriptTokenizer::new()),
+        }
+    }
+}
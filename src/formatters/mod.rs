@@ -1,40 +1,70 @@
 //! Language-specific formatters
 
 pub mod javascript;
+mod language;
+mod registry;
 
-use crate::core::error::NeatifyError;
-use crate::core::formatter::{Formatter, FormattingStats};
-use anyhow::Result;
+pub use language::Language;
+pub use registry::FormatterRegistry;
+
+use crate::core::config::ConfigResolver;
+use crate::core::error::{NeatifyError, Result};
+use crate::core::formatter::{rewrite_file, FormattingStats};
+use crate::core::options::FormatOptions;
 use std::path::Path;
 
-/// Format a file with the appropriate formatter
-pub fn format_file<P: AsRef<Path>>(file_path: P, write: bool) -> Result<bool> {
+/// Format a file with the appropriate formatter from `registry`
+///
+/// `options` are the defaults that project configuration files override.
+pub fn format_file<P: AsRef<Path>>(
+    file_path: P,
+    write: bool,
+    options: &FormatOptions,
+    registry: &FormatterRegistry,
+) -> Result<bool> {
     let file_path = file_path.as_ref();
 
     // Get the appropriate formatter for the file
-    let formatter = get_formatter_for_file(file_path);
+    let formatter = registry.formatter_for_file(file_path);
 
     match formatter {
-        Some(formatter) => formatter.format_file(file_path, write),
-        None => Err(NeatifyError::UnsupportedFile(file_path.display().to_string()).into()),
+        Some(formatter) => {
+            let options = ConfigResolver::new().resolve(file_path, options)?;
+            rewrite_file(formatter, file_path, write, &options)
+        }
+        None => Err(NeatifyError::unsupported_file(file_path)),
     }
 }
 
-/// Format all supported files in a directory
-pub fn format_directory<P: AsRef<Path>>(dir_path: P, write: bool) -> Result<FormattingStats> {
+/// Format all files in a directory that have a formatter in `registry`
+///
+/// `options` are the defaults that project configuration files override.
+pub fn format_directory<P: AsRef<Path>>(
+    dir_path: P,
+    write: bool,
+    options: &FormatOptions,
+    registry: &FormatterRegistry,
+) -> Result<FormattingStats> {
     let dir_path = dir_path.as_ref();
 
     // Check if the directory exists
     if !dir_path.exists() || !dir_path.is_dir() {
-        return Err(NeatifyError::IoError(std::io::Error::new(
-            std::io::ErrorKind::NotFound,
-            format!("Directory does not exist: {}", dir_path.display()),
-        ))
-        .into());
+        return Err(NeatifyError::io(
+            dir_path,
+            std::io::Error::new(std::io::ErrorKind::NotFound, "Directory does not exist"),
+        ));
     }
 
     let mut stats = FormattingStats::new();
-    format_directory_recursive(dir_path, write, &mut stats)?;
+    let mut resolver = ConfigResolver::new();
+    format_directory_recursive(
+        dir_path,
+        write,
+        options,
+        registry,
+        &mut resolver,
+        &mut stats,
+    )?;
 
     Ok(stats)
 }
@@ -43,29 +73,31 @@ pub fn format_directory<P: AsRef<Path>>(dir_path: P, write: bool) -> Result<Form
 fn format_directory_recursive(
     dir_path: &Path,
     write: bool,
+    options: &FormatOptions,
+    registry: &FormatterRegistry,
+    resolver: &mut ConfigResolver,
     stats: &mut FormattingStats,
 ) -> Result<()> {
-    for entry in std::fs::read_dir(dir_path)? {
-        let entry = entry?;
+    let entries = std::fs::read_dir(dir_path).map_err(|e| NeatifyError::io(dir_path, e))?;
+    for entry in entries {
+        let entry = entry.map_err(|e| NeatifyError::io(dir_path, e))?;
         let path = entry.path();
 
         if path.is_dir() {
             // Skip node_modules directory
-            if path
-                .file_name()
-                .map_or(false, |name| name == "node_modules")
-            {
+            if path.file_name().is_some_and(|name| name == "node_modules") {
                 continue;
             }
 
             // Recursively process subdirectories
-            format_directory_recursive(&path, write, stats)?;
+            format_directory_recursive(&path, write, options, registry, resolver, stats)?;
         } else if path.is_file() {
             // Try to format the file if there's a formatter available
-            if let Some(formatter) = get_formatter_for_file(&path) {
+            if let Some(formatter) = registry.formatter_for_file(&path) {
                 stats.total_files += 1;
 
-                match formatter.format_file(&path, write) {
+                let file_options = resolver.resolve(&path, options)?;
+                match rewrite_file(formatter, &path, write, &file_options) {
                     Ok(true) => {
                         if write {
                             stats.formatted_files += 1;
@@ -76,14 +108,14 @@ fn format_directory_recursive(
                     Ok(false) => {
                         // File was already formatted
                     }
+                    Err(e) if e.is_parse_error() => {
+                        // Keep going and report files that failed to parse in the stats
+                        stats.failed_files += 1;
+                        stats.errors.push(e.with_path(&path));
+                    }
                     Err(e) => {
-                        // Return error with proper error type
-                        return Err(NeatifyError::FormattingError(format!(
-                            "Error formatting {}: {}",
-                            path.display(),
-                            e
-                        ))
-                        .into());
+                        // Return the error annotated with the file it occurred in
+                        return Err(e.with_path(&path));
                     }
                 }
             }
@@ -92,19 +124,3 @@ fn format_directory_recursive(
 
     Ok(())
 }
-
-/// Get the appropriate formatter for a file
-fn get_formatter_for_file(file_path: &Path) -> Option<Box<dyn Formatter>> {
-    // Create instances of all available formatters
-    let formatters: Vec<Box<dyn Formatter>> =
-        vec![Box::new(javascript::JavaScriptFormatter::new())];
-
-    // Find the first formatter that supports this file
-    for formatter in formatters {
-        if formatter.is_supported(file_path) {
-            return Some(formatter);
-        }
-    }
-
-    None
-}
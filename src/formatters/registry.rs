@@ -0,0 +1,114 @@
+//! Registry of the formatters available for formatting files
+
+use crate::core::formatter::Formatter;
+use crate::formatters::javascript::JavaScriptFormatter;
+use std::path::Path;
+
+/// A formatter together with the files it handles
+struct Registration {
+    formatter: Box<dyn Formatter>,
+    extensions: Vec<String>,
+    file_names: Vec<String>,
+    priority: i32,
+}
+
+impl Registration {
+    /// Check if this registration handles the given file
+    fn matches(&self, file_path: &Path) -> bool {
+        // Without explicit patterns, the formatter decides by itself
+        if self.extensions.is_empty() && self.file_names.is_empty() {
+            return self.formatter.is_supported(file_path);
+        }
+
+        let file_name_matches = file_path
+            .file_name()
+            .is_some_and(|name| self.file_names.iter().any(|n| name == n.as_str()));
+        let extension_matches = file_path.extension().is_some_and(|ext| {
+            let ext = ext.to_string_lossy();
+            self.extensions.iter().any(|e| e.eq_ignore_ascii_case(&ext))
+        });
+
+        file_name_matches || extension_matches
+    }
+}
+
+/// Registry of formatters, looked up by file name and extension
+///
+/// When several formatters handle the same file, the one with the highest priority
+/// is used. Between formatters of equal priority, the most recently registered wins,
+/// so downstream crates can replace a built-in formatter.
+pub struct FormatterRegistry {
+    registrations: Vec<Registration>,
+}
+
+impl FormatterRegistry {
+    /// Priority of the built-in formatters
+    pub const BUILTIN_PRIORITY: i32 = 0;
+
+    /// Create an empty registry without any formatters
+    pub fn new() -> Self {
+        Self {
+            registrations: Vec::new(),
+        }
+    }
+
+    /// Create a registry with the built-in formatters registered
+    pub fn with_builtins() -> Self {
+        let mut registry = Self::new();
+        registry.register(
+            JavaScriptFormatter::new(),
+            &["js", "mjs", "cjs"],
+            &[],
+            Self::BUILTIN_PRIORITY,
+        );
+        registry
+    }
+
+    /// Register a formatter
+    ///
+    /// # Arguments
+    /// * `formatter` - The formatter to register
+    /// * `extensions` - File extensions handled by the formatter, without the leading dot
+    /// * `file_names` - Exact file names handled by the formatter, such as `Makefile`
+    /// * `priority` - Priority over other formatters handling the same file
+    ///
+    /// If both `extensions` and `file_names` are empty, `Formatter::is_supported`
+    /// decides which files the formatter handles.
+    pub fn register<F: Formatter + 'static>(
+        &mut self,
+        formatter: F,
+        extensions: &[&str],
+        file_names: &[&str],
+        priority: i32,
+    ) {
+        self.registrations.push(Registration {
+            formatter: Box::new(formatter),
+            extensions: extensions.iter().map(|e| e.to_string()).collect(),
+            file_names: file_names.iter().map(|n| n.to_string()).collect(),
+            priority,
+        });
+    }
+
+    /// Get the formatter for a file, if any formatter handles it
+    ///
+    /// Among formatters with the same priority, the most recently registered one wins.
+    pub fn formatter_for_file(&self, file_path: &Path) -> Option<&dyn Formatter> {
+        // `max_by_key` returns the last of equal maximums, so the latest registration
+        self.registrations
+            .iter()
+            .filter(|registration| registration.matches(file_path))
+            .max_by_key(|registration| registration.priority)
+            .map(|registration| registration.formatter.as_ref())
+    }
+
+    /// Check if any registered formatter handles a file
+    pub fn is_supported(&self, file_path: &Path) -> bool {
+        self.formatter_for_file(file_path).is_some()
+    }
+}
+
+impl Default for FormatterRegistry {
+    fn default() -> Self {
+        Self::with_builtins()
+    }
+}
@@ -9,34 +9,131 @@ mod formatters;
 #[cfg(feature = "wasm")]
 pub mod wasm;
 
-pub use core::formatter::FormattingStats;
-pub use formatters::{format_directory, format_file};
+pub use core::doc::Doc;
+pub use core::error::{LexError, LexErrorKind, Location, NeatifyError, Result};
+pub use core::formatter::{Formatter, FormattingStats};
+pub use core::options::{BraceStyle, EndOfLine, FormatOptions, QuoteStyle, TrailingCommas};
+pub use core::tokens::{CommentKind, Span, TemplateKind, Token, TokenKind, Tokenizer};
+pub use formatters::{format_directory, format_file, FormatterRegistry, Language};
 
-/// Format a file with the appropriate formatter
+use std::path::Path;
+
+/// Format a file with the appropriate built-in formatter
 ///
 /// # Arguments
 /// * `file_path` - Path to the file to format
 /// * `write` - Whether to write the formatted output back to the file
+/// * `options` - Options controlling the output style
 ///
 /// # Returns
 /// * `Ok(bool)` - `true` if the file needed formatting, `false` if it was already formatted
 /// * `Err` - If formatting failed
-pub fn format<P: AsRef<std::path::Path>>(file_path: P, write: bool) -> anyhow::Result<bool> {
-    format_file(file_path, write)
+pub fn format<P: AsRef<Path>>(file_path: P, write: bool, options: &FormatOptions) -> Result<bool> {
+    format_file(file_path, write, options, &FormatterRegistry::default())
 }
 
-/// Format all supported files in a directory
+/// Format all files in a directory that have a built-in formatter
 ///
 /// # Arguments
 /// * `dir_path` - Path to the directory to format
 /// * `write` - Whether to write the formatted output back to the files
+/// * `options` - Options controlling the output style
 ///
 /// # Returns
 /// * `Ok(FormattingStats)` - Statistics about the formatting operation
 /// * `Err` - If formatting failed
-pub fn format_dir<P: AsRef<std::path::Path>>(
+pub fn format_dir<P: AsRef<Path>>(
     dir_path: P,
     write: bool,
-) -> anyhow::Result<FormattingStats> {
-    format_directory(dir_path, write)
+    options: &FormatOptions,
+) -> Result<FormattingStats> {
+    format_directory(dir_path, write, options, &FormatterRegistry::default())
+}
+
+/// Resolve the effective formatting options for a file
+///
+/// Applies the project configuration files and glob overrides that match
+/// `file_path` on top of `base`, exactly as `format` and `format_dir` do.
+///
+/// # Arguments
+/// * `file_path` - Path to the file, which does not need to exist
+/// * `base` - Default options that configuration files override
+///
+/// # Returns
+/// * `Ok(FormatOptions)` - The options used to format the file
+/// * `Err` - If a configuration file could not be read or is invalid
+pub fn resolve_options<P: AsRef<Path>>(
+    file_path: P,
+    base: &FormatOptions,
+) -> Result<FormatOptions> {
+    core::config::ConfigResolver::new().resolve(file_path.as_ref(), base)
+}
+
+/// Format source code held in memory
+///
+/// # Arguments
+/// * `source` - The source code to format
+/// * `language` - The language of the source code
+/// * `options` - Options controlling the output style
+///
+/// # Returns
+/// * `Ok(String)` - The formatted source code
+/// * `Err` - If formatting failed
+pub fn format_str(source: &str, language: Language, options: &FormatOptions) -> Result<String> {
+    language.formatter().format(source, options)
+}
+
+/// Tokenize source code held in memory
+///
+/// Every token carries the `Span` it covers in `source`, which makes the tokens
+/// usable for error reporting and editor integration.
+///
+/// # Arguments
+/// * `source` - The source code to tokenize
+/// * `language` - The language of the source code
+///
+/// # Returns
+/// * `Ok(Vec<Token>)` - The tokens, in source order
+/// * `Err` - If the source code could not be tokenized
+pub fn tokenize(source: &str, language: Language) -> Result<Vec<Token>> {
+    Ok(language.tokenizer().tokenize(source)?)
+}
+
+/// Tokenize source code into a lossless token stream
+///
+/// Whitespace, line breaks and comments are attached to the neighbouring tokens as
+/// leading and trailing trivia, and the stream ends with a `TokenKind::EndOfFile`
+/// token. Writing every token with `Token::write_source` reproduces `source` byte
+/// for byte.
+///
+/// # Arguments
+/// * `source` - The source code to tokenize
+/// * `language` - The language of the source code
+///
+/// # Returns
+/// * `Ok(Vec<Token>)` - The tokens with their trivia, in source order
+/// * `Err` - If the source code could not be tokenized
+pub fn tokenize_lossless(source: &str, language: Language) -> Result<Vec<Token>> {
+    Ok(core::tokens::attach_trivia(tokenize(source, language)?))
+}
+
+/// Format source code held in memory, choosing the language from a file path
+///
+/// The path does not need to exist. Its extension selects the language and the
+/// project configuration that applies to it is used, as in `resolve_options`.
+///
+/// # Arguments
+/// * `source` - The source code to format
+/// * `file_path` - Path the source code belongs to
+///
+/// # Returns
+/// * `Ok(String)` - The formatted source code
+/// * `Err` - If the path has no supported language or formatting failed
+pub fn format_str_for_path<P: AsRef<Path>>(source: &str, file_path: P) -> Result<String> {
+    let file_path = file_path.as_ref();
+    let language =
+        Language::from_path(file_path).ok_or_else(|| NeatifyError::unsupported_file(file_path))?;
+    let options = resolve_options(file_path, &FormatOptions::default())?;
+
+    format_str(source, language, &options)
 }
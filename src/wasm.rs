@@ -1,6 +1,8 @@
 //! WebAssembly bindings for neatify
 
 use crate::core::formatter::FormattingStats as RustFormattingStats;
+use crate::core::options::FormatOptions;
+use crate::formatters::FormatterRegistry;
 use crate::{format_directory, format_file};
 use serde::{Deserialize, Serialize};
 use std::path::Path;
@@ -41,6 +43,7 @@ impl NeatifyError {
 pub struct FormattingStats {
     formatted_files: usize,
     files_needing_formatting: usize,
+    failed_files: usize,
     total_files: usize,
 }
 
@@ -50,11 +53,13 @@ impl FormattingStats {
     pub fn new(
         formatted_files: usize,
         files_needing_formatting: usize,
+        failed_files: usize,
         total_files: usize,
     ) -> FormattingStats {
         FormattingStats {
             formatted_files,
             files_needing_formatting,
+            failed_files,
             total_files,
         }
     }
@@ -69,6 +74,11 @@ impl FormattingStats {
         self.files_needing_formatting
     }
 
+    #[wasm_bindgen(getter)]
+    pub fn failed_files(&self) -> usize {
+        self.failed_files
+    }
+
     #[wasm_bindgen(getter)]
     pub fn total_files(&self) -> usize {
         self.total_files
@@ -80,35 +90,46 @@ impl From<RustFormattingStats> for FormattingStats {
         FormattingStats {
             formatted_files: stats.formatted_files,
             files_needing_formatting: stats.files_needing_formatting,
+            failed_files: stats.failed_files,
             total_files: stats.total_files,
         }
     }
 }
 
+/// Convert JavaScript format options into `FormatOptions`, using defaults when absent
+fn parse_options(options: JsValue) -> Result<FormatOptions, JsValue> {
+    if options.is_undefined() || options.is_null() {
+        return Ok(FormatOptions::default());
+    }
+
+    serde_wasm_bindgen::from_value(options)
+        .map_err(|e| JsValue::from_str(&format!("Invalid format options: {}", e)))
+}
+
 /// Format JavaScript code content directly
 #[wasm_bindgen]
-pub fn format_code(content: &str) -> Result<String, JsValue> {
+pub fn format_code(content: &str, options: JsValue) -> Result<String, JsValue> {
     use crate::core::formatter::Formatter;
     use crate::formatters::javascript::JavaScriptFormatter;
 
+    let options = parse_options(options)?;
     let formatter = JavaScriptFormatter::new();
-    Ok(formatter.format(content))
+    formatter
+        .format(content, &options)
+        .map_err(|e| JsValue::from_str(&format!("Error formatting code: {}", e)))
 }
 
 /// Check if a file is supported by neatify
 #[wasm_bindgen]
 pub fn is_supported(file_path: &str) -> bool {
-    use crate::core::formatter::Formatter;
-    use crate::formatters::javascript::JavaScriptFormatter;
-
-    let formatter = JavaScriptFormatter::new();
-    formatter.is_supported(Path::new(file_path))
+    FormatterRegistry::default().is_supported(Path::new(file_path))
 }
 
 /// Format a single file
 #[wasm_bindgen]
-pub fn format(file_path: &str, write: bool) -> Result<bool, JsValue> {
-    match format_file(file_path, write) {
+pub fn format(file_path: &str, write: bool, options: JsValue) -> Result<bool, JsValue> {
+    let options = parse_options(options)?;
+    match format_file(file_path, write, &options, &FormatterRegistry::default()) {
         Ok(result) => Ok(result),
         Err(e) => Err(JsValue::from_str(&format!("Error formatting file: {}", e))),
     }
@@ -116,8 +137,13 @@ pub fn format(file_path: &str, write: bool) -> Result<bool, JsValue> {
 
 /// Format all supported files in a directory
 #[wasm_bindgen]
-pub fn format_dir(dir_path: &str, write: bool) -> Result<FormattingStats, JsValue> {
-    match format_directory(dir_path, write) {
+pub fn format_dir(
+    dir_path: &str,
+    write: bool,
+    options: JsValue,
+) -> Result<FormattingStats, JsValue> {
+    let options = parse_options(options)?;
+    match format_directory(dir_path, write, &options, &FormatterRegistry::default()) {
         Ok(stats) => Ok(stats.into()),
         Err(e) => Err(JsValue::from_str(&format!(
             "Error formatting directory: {}",
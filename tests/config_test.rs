@@ -0,0 +1,206 @@
+use neatify::{format, format_dir, resolve_options, BraceStyle, FormatOptions, QuoteStyle};
+use std::fs;
+
+#[test]
+fn test_nested_config_files_are_merged() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let nested_dir = temp_dir.path().join("nested");
+    fs::create_dir(&nested_dir).unwrap();
+
+    // The outer config sets the indentation, the nested one only the quotes
+    fs::write(
+        temp_dir.path().join("neatify.toml"),
+        "root = true\nindent_width = 4\n",
+    )
+    .unwrap();
+    fs::write(
+        nested_dir.join(".neatifyrc.json"),
+        r#"{ "quote_style": "single" }"#,
+    )
+    .unwrap();
+
+    let outer_file = temp_dir.path().join("outer.js");
+    let nested_file = nested_dir.join("inner.js");
+    let source = r#"function f(){return "a";}"#;
+    fs::write(&outer_file, source).unwrap();
+    fs::write(&nested_file, source).unwrap();
+
+    let stats = format_dir(temp_dir.path(), true, &FormatOptions::default()).unwrap();
+    assert_eq!(stats.total_files, 2);
+
+    let outer = fs::read_to_string(&outer_file).unwrap();
+    assert!(outer.contains("    return \"a\";"));
+
+    let nested = fs::read_to_string(&nested_file).unwrap();
+    assert!(nested.contains("    return 'a';"));
+}
+
+#[test]
+fn test_root_config_stops_discovery() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let project_dir = temp_dir.path().join("project");
+    fs::create_dir(&project_dir).unwrap();
+
+    fs::write(temp_dir.path().join("neatify.toml"), "use_tabs = true\n").unwrap();
+    fs::write(project_dir.join("neatify.toml"), "root = true\n").unwrap();
+
+    let file_path = project_dir.join("test.js");
+    fs::write(&file_path, "function f(){return 1;}").unwrap();
+
+    format(&file_path, true, &FormatOptions::default()).unwrap();
+
+    let formatted = fs::read_to_string(&file_path).unwrap();
+    assert!(formatted.contains("  return 1;"));
+}
+
+#[test]
+fn test_unknown_config_key_reports_location() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let config_path = temp_dir.path().join("neatify.toml");
+    fs::write(
+        &config_path,
+        "root = true\nindent_width = 4\ntab_size = 8\n",
+    )
+    .unwrap();
+
+    let file_path = temp_dir.path().join("test.js");
+    fs::write(&file_path, "let a = 1;\n").unwrap();
+
+    let error = format(&file_path, false, &FormatOptions::default())
+        .unwrap_err()
+        .to_string();
+    assert!(error.contains(&format!("{}:3:1", config_path.display())));
+    assert!(error.contains("tab_size"));
+}
+
+#[test]
+fn test_glob_overrides_apply_per_file() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    fs::write(
+        temp_dir.path().join("neatify.toml"),
+        r#"root = true
+indent_width = 4
+
+[[overrides]]
+files = ["legacy/**/*.js"]
+options = { quote_style = "single" }
+
+[[overrides]]
+files = ["*.mjs"]
+excluded_files = ["vendor/**"]
+options = { use_tabs = true }
+"#,
+    )
+    .unwrap();
+
+    let root = temp_dir.path();
+    let defaults = FormatOptions::default();
+
+    let legacy = resolve_options(root.join("legacy/a/old.js"), &defaults).unwrap();
+    assert_eq!(legacy.quote_style, QuoteStyle::Single);
+    assert_eq!(legacy.indent_width, 4);
+    assert!(!legacy.use_tabs);
+
+    let module = resolve_options(root.join("packages/app/index.mjs"), &defaults).unwrap();
+    assert_eq!(module.quote_style, QuoteStyle::Double);
+    assert!(module.use_tabs);
+
+    let vendored = resolve_options(root.join("vendor/lib.mjs"), &defaults).unwrap();
+    assert!(!vendored.use_tabs);
+
+    // Overrides are applied when formatting the directory as well
+    let legacy_dir = root.join("legacy");
+    fs::create_dir(&legacy_dir).unwrap();
+    let legacy_file = legacy_dir.join("old.js");
+    fs::write(&legacy_file, r#"function f(){return "a";}"#).unwrap();
+
+    format_dir(root, true, &defaults).unwrap();
+    let formatted = fs::read_to_string(&legacy_file).unwrap();
+    assert!(formatted.contains("    return 'a';"));
+}
+
+#[test]
+fn test_overrides_in_json_config() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    fs::write(
+        temp_dir.path().join(".neatifyrc.json"),
+        r#"{
+  "root": true,
+  "brace_style": "1tbs",
+  "overrides": [{ "files": ["src/**/*.cjs"], "options": { "semicolons": false, "brace_style": "allman" } }]
+}"#,
+    )
+    .unwrap();
+
+    let options = resolve_options(
+        temp_dir.path().join("src/index.cjs"),
+        &FormatOptions::default(),
+    )
+    .unwrap();
+    assert!(!options.semicolons);
+    assert_eq!(options.brace_style, BraceStyle::Allman);
+
+    let options = resolve_options(
+        temp_dir.path().join("src/index.js"),
+        &FormatOptions::default(),
+    )
+    .unwrap();
+    assert_eq!(options.brace_style, BraceStyle::OneTbs);
+}
+
+#[test]
+fn test_nested_config_beats_parent_override() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let root = temp_dir.path();
+    let sub_dir = root.join("sub");
+    fs::create_dir(&sub_dir).unwrap();
+
+    fs::write(
+        root.join("neatify.toml"),
+        r#"root = true
+
+[[overrides]]
+files = ["*.js"]
+options = { quote_style = "single", use_tabs = true }
+"#,
+    )
+    .unwrap();
+    fs::write(sub_dir.join("neatify.toml"), "quote_style = \"double\"\n").unwrap();
+
+    let defaults = FormatOptions::default();
+
+    // The nearer config's key wins over the parent's override
+    let nested = resolve_options(sub_dir.join("a.js"), &defaults).unwrap();
+    assert_eq!(nested.quote_style, QuoteStyle::Double);
+    // Keys the nearer config does not set still come from the override
+    assert!(nested.use_tabs);
+
+    let outer = resolve_options(root.join("a.js"), &defaults).unwrap();
+    assert_eq!(outer.quote_style, QuoteStyle::Single);
+}
+
+#[test]
+fn test_parent_components_are_resolved_before_discovery() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let root = temp_dir.path();
+    let project = root.join("project");
+    fs::create_dir_all(project.join("b")).unwrap();
+    fs::create_dir_all(project.join("c")).unwrap();
+
+    fs::write(root.join("neatify.toml"), "root = true\n").unwrap();
+    fs::write(
+        project.join("neatify.toml"),
+        r#"[[overrides]]
+files = ["c/*.js"]
+options = { quote_style = "single" }
+"#,
+    )
+    .unwrap();
+    fs::write(project.join("b").join("neatify.toml"), "indent_width = 8\n").unwrap();
+
+    // `b/../c/x.js` is in `c`, so the config in `b` does not apply but the override does
+    let path = project.join("b").join("..").join("c").join("x.js");
+    let options = resolve_options(path, &FormatOptions::default()).unwrap();
+    assert_eq!(options.indent_width, FormatOptions::default().indent_width);
+    assert_eq!(options.quote_style, QuoteStyle::Single);
+}
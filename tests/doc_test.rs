@@ -0,0 +1,96 @@
+use neatify::{Doc, FormatOptions};
+
+fn options_with_width(print_width: usize) -> FormatOptions {
+    FormatOptions {
+        print_width,
+        ..FormatOptions::default()
+    }
+}
+
+fn call(name: &str, arguments: &[&str]) -> Doc {
+    Doc::group(Doc::concat(vec![
+        Doc::text(format!("{}(", name)),
+        Doc::indent(Doc::concat(vec![
+            Doc::softline(),
+            Doc::join(
+                Doc::concat(vec![Doc::text(","), Doc::line()]),
+                arguments.iter().map(|argument| Doc::text(*argument)),
+            ),
+        ])),
+        Doc::softline(),
+        Doc::text(")"),
+    ]))
+}
+
+#[test]
+fn test_group_stays_flat_when_it_fits() {
+    let doc = call("f", &["alpha", "beta"]);
+    assert_eq!(doc.print(&options_with_width(80)), "f(alpha, beta)");
+}
+
+#[test]
+fn test_group_breaks_when_it_does_not_fit() {
+    let doc = call("f", &["alpha", "beta"]);
+    assert_eq!(
+        doc.print(&options_with_width(10)),
+        "f(\n  alpha,\n  beta\n)"
+    );
+
+    // Only the outer group breaks when the inner one fits on its own line
+    let doc = call("outer", &["first", "second"]);
+    let nested = Doc::group(Doc::concat(vec![
+        Doc::text("g("),
+        Doc::indent(Doc::concat(vec![Doc::softline(), doc])),
+        Doc::softline(),
+        Doc::text(")"),
+    ]));
+    assert_eq!(
+        nested.print(&options_with_width(22)),
+        "g(\n  outer(first, second)\n)"
+    );
+}
+
+#[test]
+fn test_hard_line_breaks_enclosing_groups() {
+    let doc = Doc::group(Doc::concat(vec![
+        Doc::text("a"),
+        Doc::line(),
+        Doc::text("b"),
+        Doc::hardline(),
+        Doc::text("c"),
+    ]));
+    assert_eq!(doc.print(&options_with_width(80)), "a\nb\nc");
+}
+
+#[test]
+fn test_if_break_and_line_suffix() {
+    let doc = Doc::group(Doc::concat(vec![
+        Doc::text("["),
+        Doc::indent(Doc::concat(vec![
+            Doc::softline(),
+            Doc::text("item"),
+            Doc::if_break(Doc::text(","), Doc::nil()),
+            Doc::line_suffix(Doc::text(" // note")),
+        ])),
+        Doc::softline(),
+        Doc::text("]"),
+    ]));
+    assert_eq!(doc.print(&options_with_width(80)), "[item] // note");
+    assert_eq!(doc.print(&options_with_width(4)), "[\n  item, // note\n]");
+}
+
+#[test]
+fn test_fill_breaks_only_where_needed() {
+    let mut parts = Vec::new();
+    for word in ["one", "two", "three", "four", "five"] {
+        if !parts.is_empty() {
+            parts.push(Doc::line());
+        }
+        parts.push(Doc::text(word));
+    }
+    let doc = Doc::fill(parts);
+    assert_eq!(
+        doc.print(&options_with_width(13)),
+        "one two three\nfour five"
+    );
+}
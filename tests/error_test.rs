@@ -0,0 +1,151 @@
+use neatify::{
+    format, format_dir, format_directory, format_file, format_str, format_str_for_path,
+    FormatOptions, Formatter, FormatterRegistry, Language, LexErrorKind, Location, NeatifyError,
+};
+use std::fs;
+use std::path::Path;
+
+/// Formatter that rejects every input containing `!!`
+struct StrictFormatter;
+
+impl Formatter for StrictFormatter {
+    fn format(&self, content: &str, _options: &FormatOptions) -> neatify::Result<String> {
+        match content.find("!!") {
+            Some(offset) => Err(NeatifyError::Formatting {
+                path: None,
+                location: Some(Location::from_offset(content, offset)),
+                message: "unexpected `!!`".to_string(),
+            }),
+            None => Ok(content.to_string()),
+        }
+    }
+
+    fn is_supported(&self, file_path: &Path) -> bool {
+        file_path.extension().is_some_and(|ext| ext == "strict")
+    }
+}
+
+#[test]
+fn test_config_error_carries_location() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let config_path = temp_dir.path().join(".neatifyrc.json");
+    fs::write(&config_path, "{\n  \"root\": true,\n  \"indent\": 4\n}\n").unwrap();
+
+    let file_path = temp_dir.path().join("test.js");
+    fs::write(&file_path, "let a = 1;\n").unwrap();
+
+    let error = format(&file_path, false, &FormatOptions::default()).unwrap_err();
+    match &error {
+        NeatifyError::Config { path, location, .. } => {
+            assert_eq!(path, &config_path);
+            let location = location.expect("config error should have a location");
+            assert_eq!(location.line, 3);
+            assert_eq!(
+                &fs::read_to_string(&config_path).unwrap()[location.offset..][..1],
+                "\""
+            );
+        }
+        other => panic!("expected a config error, got {:?}", other),
+    }
+    assert_eq!(error.path(), Some(config_path.as_path()));
+
+    // The position is printed once, as a prefix
+    let message = error.to_string();
+    assert!(message.contains(&format!("{}:3:", config_path.display())));
+    assert!(!message.contains("at line"), "{}", message);
+}
+
+#[test]
+fn test_lex_error_carries_lex_error() {
+    let error = format_str(
+        "let s = 'open;\n",
+        Language::JavaScript,
+        &FormatOptions::default(),
+    )
+    .unwrap_err();
+    match &error {
+        NeatifyError::Lex { error, .. } => {
+            assert_eq!(error.kind, LexErrorKind::UnterminatedString);
+            assert_eq!((error.location.line, error.location.column), (1, 9));
+        }
+        other => panic!("expected a lex error, got {:?}", other),
+    }
+    assert!(std::error::Error::source(&error).is_some());
+    assert!(error.is_parse_error());
+}
+
+#[test]
+fn test_syntax_error_carries_location() {
+    let error = format_str(
+        "let a = (1;\n",
+        Language::JavaScript,
+        &FormatOptions::default(),
+    )
+    .unwrap_err();
+    match &error {
+        NeatifyError::Syntax {
+            location, message, ..
+        } => {
+            assert_eq!((location.line, location.column), (1, 11));
+            assert_eq!(message, "Unexpected token `;`");
+        }
+        other => panic!("expected a syntax error, got {:?}", other),
+    }
+    assert!(error.is_parse_error());
+    assert_eq!(
+        error.to_string(),
+        "Syntax error: 1:11: Unexpected token `;`"
+    );
+}
+
+#[test]
+fn test_unsupported_file_error() {
+    let error = format_str_for_path("", "notes.txt").unwrap_err();
+    assert!(matches!(error, NeatifyError::UnsupportedFile { .. }));
+    assert_eq!(error.path().unwrap().to_str(), Some("notes.txt"));
+    assert_eq!(error.location(), None);
+}
+
+#[test]
+fn test_io_errors_carry_path() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let missing_file = temp_dir.path().join("missing.js");
+    let missing_dir = temp_dir.path().join("missing");
+
+    let error = format(&missing_file, false, &FormatOptions::default()).unwrap_err();
+    assert!(matches!(error, NeatifyError::Io { .. }));
+    assert_eq!(error.path(), Some(missing_file.as_path()));
+
+    let error = format_dir(&missing_dir, false, &FormatOptions::default()).unwrap_err();
+    assert!(matches!(error, NeatifyError::Io { .. }));
+    assert_eq!(error.path(), Some(missing_dir.as_path()));
+}
+
+#[test]
+fn test_parse_failures_are_counted_separately() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    fs::write(temp_dir.path().join("neatify.toml"), "root = true\n").unwrap();
+
+    let broken_file = temp_dir.path().join("broken.strict");
+    fs::write(&broken_file, "ok\nnot !! ok\n").unwrap();
+    fs::write(temp_dir.path().join("fine.strict"), "ok\n").unwrap();
+    fs::write(temp_dir.path().join("index.js"), "let a=1;").unwrap();
+
+    let mut registry = FormatterRegistry::with_builtins();
+    registry.register(StrictFormatter, &[], &[], 0);
+
+    // A single broken file is reported as an error instead of "no change"
+    let error = format_file(&broken_file, false, &FormatOptions::default(), &registry).unwrap_err();
+    assert_eq!(error.path(), Some(broken_file.as_path()));
+    let location = error.location().unwrap();
+    assert_eq!((location.line, location.column), (2, 5));
+
+    // Directory formatting keeps going and counts the failure
+    let stats =
+        format_directory(temp_dir.path(), false, &FormatOptions::default(), &registry).unwrap();
+    assert_eq!(stats.total_files, 3);
+    assert_eq!(stats.failed_files, 1);
+    assert_eq!(stats.files_needing_formatting, 1);
+    assert_eq!(stats.errors.len(), 1);
+    assert_eq!(stats.errors[0].path(), Some(broken_file.as_path()));
+}
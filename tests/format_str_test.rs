@@ -0,0 +1,46 @@
+use neatify::{format_str, format_str_for_path, FormatOptions, Language, NeatifyError, QuoteStyle};
+use std::fs;
+
+#[test]
+fn test_format_str_formats_in_memory() {
+    let options = FormatOptions {
+        quote_style: QuoteStyle::Single,
+        ..FormatOptions::default()
+    };
+
+    let formatted = format_str(
+        r#"function test(){return "ok";}"#,
+        Language::JavaScript,
+        &options,
+    )
+    .unwrap();
+
+    assert_eq!(formatted, "function test() {\n  return 'ok';\n}\n");
+}
+
+#[test]
+fn test_format_str_for_path_picks_language_and_config() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    fs::write(
+        temp_dir.path().join("neatify.toml"),
+        "root = true\nindent_width = 4\n",
+    )
+    .unwrap();
+
+    // The file does not have to exist on disk
+    let formatted =
+        format_str_for_path("if(x){y();}", temp_dir.path().join("src/module.mjs")).unwrap();
+    assert_eq!(formatted, "if (x) {\n    y();\n}\n");
+}
+
+#[test]
+fn test_format_str_for_path_rejects_unknown_extension() {
+    let result = format_str_for_path("fn main() {}", "main.rs");
+    assert!(matches!(result, Err(NeatifyError::UnsupportedFile { .. })));
+}
+
+#[test]
+fn test_language_from_path() {
+    assert_eq!(Language::from_path("a/b.CJS"), Some(Language::JavaScript));
+    assert_eq!(Language::from_path("README.md"), None);
+}
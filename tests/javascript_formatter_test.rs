@@ -1,4 +1,7 @@
-use neatify::{format, format_dir};
+use neatify::{
+    format, format_dir, format_str, BraceStyle, EndOfLine, FormatOptions, Language, NeatifyError,
+    QuoteStyle, TrailingCommas,
+};
 use std::fs;
 
 #[test]
@@ -12,7 +15,7 @@ fn test_javascript_formatting() {
     fs::write(&file_path, unformatted_js).unwrap();
 
     // Format the file
-    let formatted = format(&file_path, true).unwrap();
+    let formatted = format(&file_path, true, &FormatOptions::default()).unwrap();
     assert!(formatted, "File should need formatting");
 
     // Read the formatted content
@@ -24,14 +27,879 @@ fn test_javascript_formatting() {
     assert!(formatted_content.contains("if (x > 3)"));
 
     // Format the file again - should return false as it's already formatted
-    let formatted_again = format(&file_path, true).unwrap();
+    let formatted_again = format(&file_path, true, &FormatOptions::default()).unwrap();
     assert!(!formatted_again, "File should not need formatting again");
 
     // Test directory formatting
-    let stats = format_dir(temp_dir.path(), true).unwrap();
+    let stats = format_dir(temp_dir.path(), true, &FormatOptions::default()).unwrap();
     assert_eq!(stats.total_files, 1, "Should process 1 file");
     assert_eq!(
         stats.formatted_files, 0,
         "Should not format any files as they're already formatted"
     );
 }
+
+#[test]
+fn test_javascript_formatting_with_options() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let file_path = temp_dir.path().join("test.js");
+
+    let unformatted_js = r#"function greet(){const name="world";return name;}"#;
+    fs::write(&file_path, unformatted_js).unwrap();
+
+    let options = FormatOptions {
+        use_tabs: true,
+        quote_style: QuoteStyle::Single,
+        ..FormatOptions::default()
+    };
+    assert!(format(&file_path, true, &options).unwrap());
+
+    let formatted_content = fs::read_to_string(&file_path).unwrap();
+    assert!(formatted_content.contains("\tconst name = 'world';"));
+    assert!(formatted_content.contains("\treturn name;"));
+
+    // Formatting with the same options again should be a no-op
+    assert!(!format(&file_path, true, &options).unwrap());
+}
+
+#[test]
+fn test_javascript_lex_errors_are_reported() {
+    let options = FormatOptions::default();
+
+    let cases = [
+        ("let a = 1;\nlet s = \"open;\n", 2, 9, "Unterminated string"),
+        (
+            "let a = 1;\n  /* never closed\n",
+            2,
+            3,
+            "Unterminated block comment",
+        ),
+        ("let a = 1;\nlet b = \u{1}2;\n", 2, 9, "Invalid character"),
+    ];
+
+    for (source, line, column, message) in cases {
+        match format_str(source, Language::JavaScript, &options) {
+            Err(NeatifyError::Lex { error, .. }) => {
+                assert_eq!(
+                    (error.location.line, error.location.column),
+                    (line, column),
+                    "{}",
+                    source
+                );
+                assert!(error.kind.to_string().contains(message), "{}", error);
+            }
+            other => panic!("expected a lex error for {:?}, got {:?}", source, other),
+        }
+    }
+}
+
+#[test]
+fn test_javascript_escaped_quotes_in_strings() {
+    let formatted = format_str(
+        r#"let s="a \"quoted\" word";"#,
+        Language::JavaScript,
+        &FormatOptions::default(),
+    )
+    .unwrap();
+    // Single quotes need no escapes here
+    assert_eq!(formatted, "let s = 'a \"quoted\" word';\n");
+}
+
+#[test]
+fn test_javascript_quote_style_picks_fewer_escapes() {
+    let source = r#"a('say "hi"');
+b("it's");
+c('plain', "both ' and \"");
+d('it\'s "x" \n');
+"#;
+    let format_with = |quote_style| {
+        let options = FormatOptions {
+            quote_style,
+            ..FormatOptions::default()
+        };
+        format_str(source, Language::JavaScript, &options).unwrap()
+    };
+
+    assert_eq!(
+        format_with(QuoteStyle::Double),
+        r#"a('say "hi"');
+b("it's");
+c("plain", "both ' and \"");
+d('it\'s "x" \n');
+"#
+    );
+    assert_eq!(
+        format_with(QuoteStyle::Single),
+        r#"a('say "hi"');
+b("it's");
+c('plain', 'both \' and "');
+d('it\'s "x" \n');
+"#
+    );
+    assert_eq!(format_with(QuoteStyle::Preserve), source);
+}
+
+#[test]
+fn test_javascript_formatting_follows_syntax_tree() {
+    let source = r#"import {a,b as c} from "./m";
+export default function f(x){for(let i=0;i<x;i++){if(i%2)continue;else g(i)}}
+const arrow=async(y)=>{await y};
+class K extends B{static n=1;constructor(){super()}get v(){return this.n}}
+"#;
+    let expected = r#"import { a, b as c } from "./m";
+export default function f(x) {
+  for (let i = 0; i < x; i++) {
+    if (i % 2) continue;
+    else g(i);
+  }
+}
+const arrow = async (y) => {
+  await y;
+};
+class K extends B {
+  static n = 1;
+  constructor() {
+    super();
+  }
+  get v() {
+    return this.n;
+  }
+}
+"#;
+
+    let formatted = format_str(source, Language::JavaScript, &FormatOptions::default()).unwrap();
+    assert_eq!(formatted, expected);
+
+    // Formatting is stable
+    let again = format_str(&formatted, Language::JavaScript, &FormatOptions::default()).unwrap();
+    assert_eq!(again, formatted);
+}
+
+#[test]
+fn test_javascript_statement_comments_and_blank_lines_are_kept() {
+    let source =
+        "// header\nlet a = 1; // one\n\n\n/* two */\nlet b = 2;\nfunction f() {\n  // empty\n}\n";
+    let expected =
+        "// header\nlet a = 1; // one\n\n/* two */\nlet b = 2;\nfunction f() {\n  // empty\n}\n";
+
+    let formatted = format_str(source, Language::JavaScript, &FormatOptions::default()).unwrap();
+    assert_eq!(formatted, expected);
+}
+
+#[test]
+fn test_javascript_line_terminators_are_line_breaks() {
+    let options = FormatOptions::default();
+    for source in [
+        "a();\r\rb();\r",
+        "a();\r\n\r\nb();\r\n",
+        "a();\u{2028}\u{2028}b();",
+        "a();\u{2029}\u{2029}b();",
+    ] {
+        let formatted = format_str(source, Language::JavaScript, &options).unwrap();
+        assert_eq!(formatted, "a();\n\nb();\n", "{:?}", source);
+    }
+
+    let error = format_str("a();\r\r  if (a {}", Language::JavaScript, &options).unwrap_err();
+    let location = error.location().unwrap();
+    assert_eq!((location.line, location.column), (3, 9));
+
+    // Line breaks inside block comments follow `end_of_line` too
+    let options = FormatOptions {
+        end_of_line: EndOfLine::Crlf,
+        ..FormatOptions::default()
+    };
+    for source in [
+        "/* a\r\n b */\r\nx;\r\n",
+        "/* a\r b */\rx;\r",
+        "/* a\n b */\nx;\n",
+    ] {
+        let formatted = format_str(source, Language::JavaScript, &options).unwrap();
+        assert_eq!(formatted, "/* a\r\n b */\r\nx;\r\n", "{:?}", source);
+    }
+}
+
+#[test]
+fn test_javascript_syntax_errors_are_reported() {
+    let error = format_str(
+        "let a = 1;\nif (a {\n}\n",
+        Language::JavaScript,
+        &FormatOptions::default(),
+    )
+    .unwrap_err();
+
+    assert!(matches!(error, NeatifyError::Syntax { .. }));
+    let location = error.location().unwrap();
+    assert_eq!((location.line, location.column), (2, 7));
+    assert!(error.to_string().contains("Unexpected token `{`"));
+}
+
+#[test]
+fn test_javascript_hashbang_is_kept() {
+    let source = "#!/usr/bin/env node\nconst a=1\n";
+    let expected = "#!/usr/bin/env node\nconst a = 1;\n";
+
+    let formatted = format_str(source, Language::JavaScript, &FormatOptions::default()).unwrap();
+    assert_eq!(formatted, expected);
+
+    // Only the first line of a file can be a hashbang
+    assert!(format_str("a;\n#!b\n", Language::JavaScript, &FormatOptions::default()).is_err());
+}
+
+#[test]
+fn test_javascript_yield_and_await_depend_on_context() {
+    let source = r#"var yield=1;var await=yield+1;await(x);
+function*g(){yield 1;const f=()=>{var yield=2}}
+async function f(){await x;for await(const a of b){}}
+"#;
+    let expected = r#"var yield = 1;
+var await = yield + 1;
+await(x);
+function* g() {
+  yield 1;
+  const f = () => {
+    var yield = 2;
+  };
+}
+async function f() {
+  await x;
+  for await (const a of b) {}
+}
+"#;
+    let options = FormatOptions::default();
+    assert_eq!(
+        format_str(source, Language::JavaScript, &options).unwrap(),
+        expected
+    );
+
+    // Modules reserve both words and allow `await` at the top level
+    let module = "import a from \"a\";\nawait a;\n";
+    assert_eq!(
+        format_str(module, Language::JavaScript, &options).unwrap(),
+        module
+    );
+    assert_eq!(
+        format_str("const r=await fetch(u)", Language::JavaScript, &options).unwrap(),
+        "const r = await fetch(u);\n"
+    );
+    assert!(format_str("export var await = 1;", Language::JavaScript, &options).is_err());
+    assert!(format_str(
+        "function* g() { var yield; }",
+        Language::JavaScript,
+        &options
+    )
+    .is_err());
+}
+
+#[test]
+fn test_javascript_deep_nesting_is_a_syntax_error() {
+    let options = FormatOptions::default();
+    let parens = format!("x = {}1{};", "(".repeat(3000), ")".repeat(3000));
+    let arrays = format!("x = {}{};", "[".repeat(2000), "]".repeat(2000));
+    // Chains nest the syntax tree without nesting the source
+    let calls = format!("x = a{};", ".b()".repeat(10000));
+    let sums = format!("x = a{};", " + a".repeat(20000));
+    let powers = format!("x = a{};", " ** a".repeat(20000));
+
+    for source in [parens, arrays, calls, sums, powers] {
+        let error = format_str(&source, Language::JavaScript, &options).unwrap_err();
+        assert!(matches!(error, NeatifyError::Syntax { .. }));
+        assert!(
+            error.to_string().contains("Nesting is too deep"),
+            "{}",
+            error
+        );
+    }
+
+    let nested = format!("x = {}1{};\n", "(".repeat(20), ")".repeat(20));
+    assert_eq!(
+        format_str(&nested, Language::JavaScript, &options).unwrap(),
+        nested
+    );
+
+    // Realistically deep code is formatted
+    let callbacks = format!(
+        "{}{}",
+        "foo(function () {\n".repeat(100),
+        "});\n".repeat(100)
+    );
+    let blocks = format!("{}x();\n{}", "if (a) {\n".repeat(200), "}\n".repeat(200));
+    let calls = format!("x = a{};\n", ".b()".repeat(300));
+    for source in [callbacks, blocks, calls] {
+        let formatted = format_str(&source, Language::JavaScript, &options).unwrap();
+        assert_eq!(
+            format_str(&formatted, Language::JavaScript, &options).unwrap(),
+            formatted
+        );
+    }
+}
+
+#[test]
+fn test_javascript_with_statements_in_scripts() {
+    let options = FormatOptions::default();
+    let formatted = format_str(
+        "with(o){a=1}\nwith (Math) x=cos(0)\n",
+        Language::JavaScript,
+        &options,
+    )
+    .unwrap();
+    assert_eq!(
+        formatted,
+        "with (o) {\n  a = 1;\n}\nwith (Math) x = cos(0);\n"
+    );
+
+    // Modules are strict mode code, which has no `with`
+    let error = format_str(
+        "import a from \"a\";\nwith (a) {}\n",
+        Language::JavaScript,
+        &options,
+    )
+    .unwrap_err();
+    assert!(matches!(error, NeatifyError::Syntax { .. }));
+}
+
+#[test]
+fn test_javascript_long_lines_break_at_print_width() {
+    let source = r#"const result = compute(firstArgument, secondArgument, thirdArgument, fourthArgument);
+const primes = [2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37, 41, 43, 47, 53, 59, 61, 67, 71, 73];
+if (isEnabled && hasPermission && !isBlocked && isWithinQuota && requestIsValid(request)) {
+  handle(request);
+}
+function ok() {
+  return firstConditionIsMet && secondConditionIsMet && thirdConditionIsMet(now);
+}
+"#;
+    let expected = r#"const result = compute(
+  firstArgument,
+  secondArgument,
+  thirdArgument,
+  fourthArgument
+);
+const primes = [
+  2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37, 41, 43, 47, 53, 59, 61, 67, 71, 73
+];
+if (
+  isEnabled &&
+  hasPermission &&
+  !isBlocked &&
+  isWithinQuota &&
+  requestIsValid(request)
+) {
+  handle(request);
+}
+function ok() {
+  return (
+    firstConditionIsMet && secondConditionIsMet && thirdConditionIsMet(now)
+  );
+}
+"#;
+
+    let formatted = format_str(source, Language::JavaScript, &FormatOptions::default()).unwrap();
+    assert_eq!(formatted, expected);
+
+    let again = format_str(&formatted, Language::JavaScript, &FormatOptions::default()).unwrap();
+    assert_eq!(again, formatted);
+}
+
+#[test]
+fn test_javascript_print_width_is_configurable() {
+    let options = FormatOptions {
+        print_width: 30,
+        trailing_commas: TrailingCommas::All,
+        ..FormatOptions::default()
+    };
+
+    let formatted = format_str(
+        "run(alpha, beta, gamma, delta);\nshort(a, b);\n",
+        Language::JavaScript,
+        &options,
+    )
+    .unwrap();
+    assert_eq!(
+        formatted,
+        "run(\n  alpha,\n  beta,\n  gamma,\n  delta,\n);\nshort(a, b);\n"
+    );
+}
+
+#[test]
+fn test_javascript_block_comments_keep_their_delimiters() {
+    let source = "/**\n     * Adds numbers.\n     * @param {number} a\n     */\nfunction add(a, /* second */ b) {\n    /**\n * Inner\n */\n  return a + /* plus */ b;\n}\nlet y = /* inline */ 2;\n";
+    let expected = "/**\n * Adds numbers.\n * @param {number} a\n */\nfunction add(a, /* second */ b) {\n  /**\n   * Inner\n   */\n  return a + /* plus */ b;\n}\nlet y = /* inline */ 2;\n";
+
+    let formatted = format_str(source, Language::JavaScript, &FormatOptions::default()).unwrap();
+    assert_eq!(formatted, expected);
+
+    let again = format_str(&formatted, Language::JavaScript, &FormatOptions::default()).unwrap();
+    assert_eq!(again, formatted);
+}
+
+#[test]
+fn test_javascript_comments_are_attached_to_nearby_code() {
+    let source = r#"const x = { // after brace
+  a: 1, // after comma
+  b: 2
+  // dangling
+};
+call(a, // first
+  b /* second */, c);
+call(/* nothing */);
+function f(a /* one */, b) {
+  return /* early */;
+}
+import { a, /* b */ b } from "x"; // import
+switch (x) {
+  case 1: // on case
+    break;
+}
+"#;
+    let expected = r#"const x = {
+  // after brace
+  a: 1, // after comma
+  b: 2
+  // dangling
+};
+call(
+  a, // first
+  b /* second */,
+  c
+);
+call(/* nothing */);
+function f(a /* one */, b) {
+  return; /* early */
+}
+import { a, /* b */ b } from "x"; // import
+switch (x) {
+  case 1: // on case
+    break;
+}
+"#;
+
+    let formatted = format_str(source, Language::JavaScript, &FormatOptions::default()).unwrap();
+    assert_eq!(formatted, expected);
+
+    let again = format_str(&formatted, Language::JavaScript, &FormatOptions::default()).unwrap();
+    assert_eq!(again, formatted);
+}
+
+#[test]
+fn test_javascript_comments_stay_with_the_code_they_follow() {
+    let cases = [
+        // Member chain link
+        ("x = a.b // c\n.c()\n", "x = a.b // c\n  .c();\n"),
+        ("f /* c */ (1);\n", "f /* c */(1);\n"),
+        // Parenthesized return argument
+        (
+            "function f() {\n  return ( // c\n a + b)\n}\n",
+            "function f() {\n  return (\n    // c\n    a + b\n  );\n}\n",
+        ),
+        // If test
+        ("if (a /* c */) {}\n", "if (a /* c */) {}\n"),
+        // Binary operands
+        ("x = 1 /* x */ + 2;\n", "x = 1 /* x */ + 2;\n"),
+        ("x = a + // plus\n  b;\n", "x =\n  a + // plus\n  b;\n"),
+        // Function body
+        (
+            "x = function () /* c */ {};\n",
+            "x = function () /* c */ {};\n",
+        ),
+        (
+            "function g() // c\n{ a() }\n",
+            "function g() {\n  // c\n  a();\n}\n",
+        ),
+        // Conditional branches
+        (
+            "x = a // c0\n  ? b // c1\n  : c; // c2\n",
+            "x = a // c0\n  ? b // c1\n  : c; // c2\n",
+        ),
+        // Switch clauses
+        (
+            "switch (x) {\ncase 1: // one\na();\ndefault: // fallthrough\nb();\n}\n",
+            "switch (x) {\n  case 1: // one\n    a();\n  default: // fallthrough\n    b();\n}\n",
+        ),
+        (
+            "switch (x) {\ncase 1:\na();\ndefault:\n// nothing\n}\n",
+            "switch (x) {\n  case 1:\n    a();\n  default:\n    // nothing\n}\n",
+        ),
+        (
+            "switch (x) {\ncase 1 /* a */: /* b */\ndefault /* c */: /* d */ y();\n}\n",
+            "switch (x) {\n  case 1: /* a */ /* b */\n  default: /* c */ /* d */\n    y();\n}\n",
+        ),
+    ];
+
+    let options = FormatOptions::default();
+    for (source, expected) in cases {
+        let formatted = format_str(source, Language::JavaScript, &options).unwrap();
+        assert_eq!(formatted, expected, "{:?}", source);
+
+        let again = format_str(&formatted, Language::JavaScript, &options).unwrap();
+        assert_eq!(again, formatted, "{:?}", source);
+    }
+}
+
+#[test]
+fn test_javascript_template_literals_keep_their_text() {
+    let source = "const a = `hello ${ name },   ${count+1}\n  {not code}  `;\nconst b = tag`outer ${ `inner ${ f(x,y) }` } end`;\n";
+    let expected = "const a = `hello ${name},   ${count + 1}\n  {not code}  `;\nconst b = tag`outer ${`inner ${f(x, y)}`} end`;\n";
+
+    let formatted = format_str(source, Language::JavaScript, &FormatOptions::default()).unwrap();
+    assert_eq!(formatted, expected);
+
+    // A substitution that has to break is indented with the code around it
+    let source = "function f() {\nif (x) {\ns = `a ${items.map(i => { return i })} b`;\n}\n}\n";
+    let expected = "function f() {\n  if (x) {\n    s = `a ${items.map(i => {\n      return i;\n    })} b`;\n  }\n}\n";
+    let formatted = format_str(source, Language::JavaScript, &FormatOptions::default()).unwrap();
+    assert_eq!(formatted, expected);
+    let again = format_str(&formatted, Language::JavaScript, &FormatOptions::default()).unwrap();
+    assert_eq!(again, formatted);
+}
+
+#[test]
+fn test_javascript_template_line_breaks_follow_end_of_line() {
+    for end_of_line in [EndOfLine::Crlf, EndOfLine::Auto] {
+        let options = FormatOptions {
+            end_of_line,
+            ..FormatOptions::default()
+        };
+        let source = "let s = `x\r\ny\rz ${a}\r\n`;\r\nlet t = \"a\\\r\nb\";\r\n";
+        let expected = "let s = `x\r\ny\r\nz ${a}\r\n`;\r\nlet t = \"a\\\r\nb\";\r\n";
+
+        let formatted = format_str(source, Language::JavaScript, &options).unwrap();
+        assert_eq!(formatted, expected, "{:?}", end_of_line);
+        let again = format_str(&formatted, Language::JavaScript, &options).unwrap();
+        assert_eq!(again, formatted);
+    }
+}
+
+#[test]
+fn test_javascript_regex_literals_are_kept_verbatim() {
+    let source =
+        "const re=/\\/\\/ [a-z/]+ \"x\"/gi;\nlet n=a/b/c;\nif(/^\\s*$/.test(line))skip();\n";
+    let expected = "const re = /\\/\\/ [a-z/]+ \"x\"/gi;\nlet n = a / b / c;\nif (/^\\s*$/.test(line)) skip();\n";
+
+    let formatted = format_str(source, Language::JavaScript, &FormatOptions::default()).unwrap();
+    assert_eq!(formatted, expected);
+}
+
+#[test]
+fn test_javascript_numbers_can_be_normalized() {
+    let source =
+        "let a = [0XFF, 0xabn, 0B1010, 1_000, 1.50, 1.0, 2.0E+10];\nlet b = 1.0.toFixed(2);\n";
+
+    let formatted = format_str(source, Language::JavaScript, &FormatOptions::default()).unwrap();
+    assert_eq!(formatted, source);
+
+    let options = FormatOptions {
+        normalize_numbers: true,
+        ..FormatOptions::default()
+    };
+    let formatted = format_str(source, Language::JavaScript, &options).unwrap();
+    assert_eq!(
+        formatted,
+        "let a = [0xFF, 0xABn, 0b1010, 1_000, 1.5, 1, 2e+10];\nlet b = (1).toFixed(2);\n"
+    );
+}
+
+#[test]
+fn test_javascript_unicode_identifiers_are_kept() {
+    let source = "const café={π:3.14};\nlet \\u{62}ar=café.π;\u{2028}export{café as 変数}\n";
+    let expected = "const café = { π: 3.14 };\nlet \\u{62}ar = café.π;\nexport { café as 変数 };\n";
+
+    let formatted = format_str(source, Language::JavaScript, &FormatOptions::default()).unwrap();
+    assert_eq!(formatted, expected);
+}
+
+#[test]
+fn test_javascript_modern_operators() {
+    let source = r#"let v=a?.b?.[c]?.(d)??e, w=t?.5:1, n=a=-1;
+x**=2;y&&=z;y||=z;y??=z;p=2**-1;
+const g=(...args)=>!args;
+class C{#count=0;static #s;#m(){return this.#count} has(o){return #count in o}}
+"#;
+    let expected = r#"let v = a?.b?.[c]?.(d) ?? e,
+  w = t ? .5 : 1,
+  n = a = -1;
+x **= 2;
+y &&= z;
+y ||= z;
+y ??= z;
+p = 2 ** -1;
+const g = (...args) => !args;
+class C {
+  #count = 0;
+  static #s;
+  #m() {
+    return this.#count;
+  }
+  has(o) {
+    return #count in o;
+  }
+}
+"#;
+
+    let formatted = format_str(source, Language::JavaScript, &FormatOptions::default()).unwrap();
+    assert_eq!(formatted, expected);
+}
+
+#[test]
+fn test_javascript_objects_stay_inline_when_short() {
+    let source = r#"const o = {a:1, b:[1,2], c:{d:2}};
+const kept = {
+  a: 1, b: 2};
+foo({}); bar({a}, {b:1}); const {x, y: [z]} = o;
+function f({
+  a, b}) { return {...a, b}; }
+const long = { alpha: alphaValue, beta: betaValue, gamma: gammaValue, delta: deltaValue };
+"#;
+    let expected = r#"const o = { a: 1, b: [1, 2], c: { d: 2 } };
+const kept = {
+  a: 1,
+  b: 2
+};
+foo({});
+bar({ a }, { b: 1 });
+const { x, y: [z] } = o;
+function f({ a, b }) {
+  return { ...a, b };
+}
+const long = {
+  alpha: alphaValue,
+  beta: betaValue,
+  gamma: gammaValue,
+  delta: deltaValue
+};
+"#;
+
+    let formatted = format_str(source, Language::JavaScript, &FormatOptions::default()).unwrap();
+    assert_eq!(formatted, expected);
+
+    let again = format_str(&formatted, Language::JavaScript, &FormatOptions::default()).unwrap();
+    assert_eq!(again, formatted);
+}
+
+#[test]
+fn test_javascript_semicolons_follow_asi_rules() {
+    let source = r#"for (let i=0;i<n;i++) ;
+;;let a = 1;;
+let t = x
+;[1, 2].forEach(g)
+;(() => 1)()
+;`t`.length
+;-y
+class M { a = 1; [b] = 2; c; *gen() {} get; f() {} #l; ; m = 3 }
+"#;
+    let with_semicolons = r#"for (let i = 0; i < n; i++);
+let a = 1;
+let t = x;
+[1, 2].forEach(g);
+(() => 1)();
+`t`.length;
+-y;
+class M {
+  a = 1;
+  [b] = 2;
+  c;
+  *gen() {}
+  get;
+  f() {}
+  #l;
+  m = 3;
+}
+"#;
+    let without_semicolons = r#"for (let i = 0; i < n; i++);
+let a = 1
+let t = x
+;[1, 2].forEach(g)
+;(() => 1)()
+;`t`.length
+;-y
+class M {
+  a = 1;
+  [b] = 2
+  c;
+  *gen() {}
+  get;
+  f() {}
+  #l
+  m = 3
+}
+"#;
+
+    let formatted = format_str(source, Language::JavaScript, &FormatOptions::default()).unwrap();
+    assert_eq!(formatted, with_semicolons);
+
+    let options = FormatOptions {
+        semicolons: false,
+        ..FormatOptions::default()
+    };
+    let formatted = format_str(source, Language::JavaScript, &options).unwrap();
+    assert_eq!(formatted, without_semicolons);
+
+    // The guards keep the statements apart when formatting again
+    let again = format_str(&formatted, Language::JavaScript, &options).unwrap();
+    assert_eq!(again, formatted);
+}
+
+#[test]
+fn test_javascript_brace_styles() {
+    let source =
+        "if(a){b()}else{c()}\ntry{d()}catch(e){f()}finally{g()}\ndo{h()}while(i)\nfunction j(){}\n";
+    let format_with = |brace_style| {
+        let options = FormatOptions {
+            brace_style,
+            ..FormatOptions::default()
+        };
+        format_str(source, Language::JavaScript, &options).unwrap()
+    };
+
+    assert_eq!(
+        format_with(BraceStyle::OneTbs),
+        r#"if (a) {
+  b();
+} else {
+  c();
+}
+try {
+  d();
+} catch (e) {
+  f();
+} finally {
+  g();
+}
+do {
+  h();
+} while (i);
+function j() {}
+"#
+    );
+    assert_eq!(
+        format_with(BraceStyle::Stroustrup),
+        r#"if (a) {
+  b();
+}
+else {
+  c();
+}
+try {
+  d();
+}
+catch (e) {
+  f();
+}
+finally {
+  g();
+}
+do {
+  h();
+} while (i);
+function j() {}
+"#
+    );
+    assert_eq!(
+        format_with(BraceStyle::Allman),
+        r#"if (a)
+{
+  b();
+}
+else
+{
+  c();
+}
+try
+{
+  d();
+}
+catch (e)
+{
+  f();
+}
+finally
+{
+  g();
+}
+do
+{
+  h();
+}
+while (i);
+function j() {}
+"#
+    );
+}
+
+#[test]
+fn test_javascript_operator_spacing_follows_syntax() {
+    let source = "function f(){return -x}\na++;--b\nif(!a){c=~d}\nx=- -y;z=+ +w;q=a- -b;r=a+ ++b;s=a++ +b;v=a+-b;t=!!a\ntypeof x;void 0;delete a.b;k=!a?-1:+1;p=a**-b;w=(-a)**b\na\n++b\n";
+    let expected = r#"function f() {
+  return -x;
+}
+a++;
+--b;
+if (!a) {
+  c = ~d;
+}
+x = - -y;
+z = + +w;
+q = a - -b;
+r = a + ++b;
+s = a++ + b;
+v = a + -b;
+t = !!a;
+typeof x;
+void 0;
+delete a.b;
+k = !a ? -1 : +1;
+p = a ** -b;
+w = (-a) ** b;
+a;
+++b;
+"#;
+
+    let formatted = format_str(source, Language::JavaScript, &FormatOptions::default()).unwrap();
+    assert_eq!(formatted, expected);
+
+    // A unary operand of `**` needs parentheses
+    let error = format_str(
+        "x = -a ** 2;",
+        Language::JavaScript,
+        &FormatOptions::default(),
+    )
+    .unwrap_err();
+    assert!(error.to_string().contains("Unexpected token `**`"));
+}
+
+#[test]
+fn test_javascript_conditional_chains_break_and_indent() {
+    let source = r#"const message = i % 3 === 0 && i % 5 === 0 ? "fizzbuzz" : i % 3 === 0 ? "fizz" : i % 5 === 0 ? "buzz" : String(i);
+const short = a ? b : c;
+function view(){return isLoading ? renderSpinnerWithAVeryLongName() : hasError ? renderError(error) : renderContent(data)}
+outer : for(;;){switch(k){case a?1:2 : break outer}}
+"#;
+    let expected = r#"const message =
+  i % 3 === 0 && i % 5 === 0
+    ? "fizzbuzz"
+    : i % 3 === 0
+      ? "fizz"
+      : i % 5 === 0
+        ? "buzz"
+        : String(i);
+const short = a ? b : c;
+function view() {
+  return isLoading
+    ? renderSpinnerWithAVeryLongName()
+    : hasError
+      ? renderError(error)
+      : renderContent(data);
+}
+outer: for (;;) {
+  switch (k) {
+    case a ? 1 : 2:
+      break outer;
+  }
+}
+"#;
+
+    let formatted = format_str(source, Language::JavaScript, &FormatOptions::default()).unwrap();
+    assert_eq!(formatted, expected);
+
+    let again = format_str(&formatted, Language::JavaScript, &FormatOptions::default()).unwrap();
+    assert_eq!(again, formatted);
+}
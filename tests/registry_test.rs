@@ -0,0 +1,107 @@
+use neatify::{format_directory, format_file, FormatOptions, Formatter, FormatterRegistry};
+use std::fs;
+use std::path::Path;
+
+/// Formatter for a toy DSL that trims trailing whitespace from every line
+struct TrimFormatter;
+
+impl Formatter for TrimFormatter {
+    fn format(&self, content: &str, _options: &FormatOptions) -> neatify::Result<String> {
+        Ok(content
+            .lines()
+            .map(|line| format!("{}\n", line.trim_end()))
+            .collect())
+    }
+
+    fn is_supported(&self, file_path: &Path) -> bool {
+        file_path.extension().is_some_and(|ext| ext == "dsl")
+    }
+}
+
+/// Formatter that leaves everything untouched
+struct IdentityFormatter;
+
+impl Formatter for IdentityFormatter {
+    fn format(&self, content: &str, _options: &FormatOptions) -> neatify::Result<String> {
+        Ok(content.to_string())
+    }
+
+    fn is_supported(&self, _file_path: &Path) -> bool {
+        true
+    }
+}
+
+#[test]
+fn test_custom_formatter_alongside_builtins() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    fs::write(temp_dir.path().join("neatify.toml"), "root = true\n").unwrap();
+
+    let dsl_file = temp_dir.path().join("rules.dsl");
+    let build_file = temp_dir.path().join("Rulefile");
+    let js_file = temp_dir.path().join("index.js");
+    fs::write(&dsl_file, "rule a   \nrule b\t\n").unwrap();
+    fs::write(&build_file, "target  \n").unwrap();
+    fs::write(&js_file, "let a=1;").unwrap();
+
+    let mut registry = FormatterRegistry::with_builtins();
+    registry.register(TrimFormatter, &["dsl"], &["Rulefile"], 0);
+
+    let stats =
+        format_directory(temp_dir.path(), true, &FormatOptions::default(), &registry).unwrap();
+    assert_eq!(stats.total_files, 3);
+    assert_eq!(stats.formatted_files, 3);
+
+    assert_eq!(fs::read_to_string(&dsl_file).unwrap(), "rule a\nrule b\n");
+    assert_eq!(fs::read_to_string(&build_file).unwrap(), "target\n");
+    assert_eq!(fs::read_to_string(&js_file).unwrap(), "let a = 1;\n");
+}
+
+#[test]
+fn test_higher_priority_formatter_wins() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    fs::write(temp_dir.path().join("neatify.toml"), "root = true\n").unwrap();
+
+    let js_file = temp_dir.path().join("index.js");
+    fs::write(&js_file, "let a=1;").unwrap();
+
+    let mut registry = FormatterRegistry::with_builtins();
+    registry.register(IdentityFormatter, &["js"], &[], 10);
+
+    let changed = format_file(&js_file, true, &FormatOptions::default(), &registry).unwrap();
+    assert!(!changed);
+    assert_eq!(fs::read_to_string(&js_file).unwrap(), "let a=1;");
+}
+
+#[test]
+fn test_latest_formatter_wins_on_equal_priority() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    fs::write(temp_dir.path().join("neatify.toml"), "root = true\n").unwrap();
+
+    let js_file = temp_dir.path().join("index.js");
+    fs::write(&js_file, "let a=1;").unwrap();
+
+    // Registering at the built-in priority replaces the built-in formatter
+    let mut registry = FormatterRegistry::with_builtins();
+    registry.register(
+        IdentityFormatter,
+        &["js"],
+        &[],
+        FormatterRegistry::BUILTIN_PRIORITY,
+    );
+
+    let changed = format_file(&js_file, true, &FormatOptions::default(), &registry).unwrap();
+    assert!(!changed);
+    assert_eq!(fs::read_to_string(&js_file).unwrap(), "let a=1;");
+}
+
+#[test]
+fn test_empty_registry_supports_nothing() {
+    let registry = FormatterRegistry::new();
+    assert!(!registry.is_supported(Path::new("index.js")));
+    assert!(FormatterRegistry::default().is_supported(Path::new("index.js")));
+
+    let mut registry = FormatterRegistry::new();
+    registry.register(TrimFormatter, &[], &[], 0);
+    assert!(registry.is_supported(Path::new("a.dsl")));
+    assert!(!registry.is_supported(Path::new("a.js")));
+}
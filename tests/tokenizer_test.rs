@@ -0,0 +1,377 @@
+use neatify::{tokenize, tokenize_lossless, CommentKind, Language, Span, TemplateKind, TokenKind};
+
+#[test]
+fn test_tokens_carry_spans() {
+    let source = "let a = \"é\";\n  foo(a) // done\n";
+    let tokens = tokenize(source, Language::JavaScript).unwrap();
+
+    let significant: Vec<_> = tokens
+        .iter()
+        .filter(|token| !matches!(token.kind, TokenKind::Whitespace(_) | TokenKind::Newline))
+        .collect();
+
+    let texts: Vec<_> = significant
+        .iter()
+        .map(|token| token.span.text(source))
+        .collect();
+    assert_eq!(
+        texts,
+        ["let", "a", "=", "\"é\"", ";", "foo", "(", "a", ")", "// done"]
+    );
+
+    // The semicolon comes after a multi-byte character
+    assert_eq!(
+        significant[4].span,
+        Span {
+            start: 12,
+            end: 13,
+            line: 1,
+            column: 12,
+        }
+    );
+
+    let foo = significant[5];
+    assert_eq!(foo.kind, TokenKind::Identifier("foo".to_string()));
+    assert_eq!((foo.span.line, foo.span.column), (2, 3));
+    assert_eq!(foo.span.location().offset, 16);
+
+    let comment = significant[9];
+    assert_eq!(
+        comment.kind,
+        TokenKind::Comment(CommentKind::Line, " done".to_string())
+    );
+    assert_eq!((comment.span.line, comment.span.column), (2, 10));
+}
+
+#[test]
+fn test_token_spans_are_ordered_and_cover_the_source() {
+    let source = "/* a\n b */ if (x) {\n\ty = 1.5e3;\n}\n";
+    let tokens = tokenize(source, Language::JavaScript).unwrap();
+
+    let mut offset = 0;
+    for token in &tokens {
+        assert_eq!(token.span.start, offset, "gap before {:?}", token);
+        offset = token.span.end;
+    }
+    assert_eq!(offset, source.len());
+
+    let if_token = &tokens[2];
+    assert_eq!(if_token.kind, TokenKind::Keyword("if".to_string()));
+    assert_eq!((if_token.span.line, if_token.span.column), (2, 7));
+}
+
+#[test]
+fn test_every_line_terminator_ends_a_line() {
+    for (source, newline) in [
+        ("a\rb", "\r"),
+        ("a\r\nb", "\r\n"),
+        ("a\u{2028}b", "\u{2028}"),
+        ("a\u{2029}b", "\u{2029}"),
+    ] {
+        let tokens = tokenize(source, Language::JavaScript).unwrap();
+        assert_eq!(tokens[1].kind, TokenKind::Newline, "{:?}", source);
+        assert_eq!(tokens[1].span.text(source), newline);
+        assert_eq!((tokens[2].span.line, tokens[2].span.column), (2, 1));
+    }
+
+    // `\r\n` inside a comment is a single line break
+    let source = "/* a\r\n\r b */ c\u{2028}\r\nd";
+    let tokens = tokenize(source, Language::JavaScript).unwrap();
+    let c = &tokens[2];
+    assert_eq!(c.kind, TokenKind::Identifier("c".to_string()));
+    assert_eq!((c.span.line, c.span.column), (3, 7));
+    let d = tokens.last().unwrap();
+    assert_eq!((d.span.line, d.span.column), (5, 1));
+
+    let tokens = tokenize_lossless("a\r\r", Language::JavaScript).unwrap();
+    let eof = tokens.last().unwrap();
+    assert_eq!(eof.kind, TokenKind::EndOfFile);
+    assert_eq!((eof.span.line, eof.span.column), (3, 1));
+
+    // Strings may hold LINE SEPARATOR and PARAGRAPH SEPARATOR but not a carriage return
+    assert!(tokenize("'a\u{2028}\u{2029}b'", Language::JavaScript).is_ok());
+    match tokenize("'a\rb'", Language::JavaScript) {
+        Err(neatify::NeatifyError::Lex { error, .. }) => {
+            assert_eq!(error.kind, neatify::LexErrorKind::UnterminatedString)
+        }
+        other => panic!("expected an unterminated string, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_lossless_tokens_reproduce_the_source() {
+    let sources = [
+        "",
+        "\n\n",
+        "let a = 'single';  // trailing\r\n\r\n\t/* block\n * comment */ b();",
+        "function f(x) {\n\n  return x * 2; /* why */\n}\n// end",
+    ];
+
+    for source in sources {
+        let tokens = tokenize_lossless(source, Language::JavaScript).unwrap();
+        assert_eq!(tokens.last().unwrap().kind, TokenKind::EndOfFile);
+
+        let mut rebuilt = String::new();
+        for token in &tokens {
+            token.write_source(&mut rebuilt);
+        }
+        assert_eq!(rebuilt, source);
+    }
+}
+
+#[test]
+fn test_lossless_trivia_attachment() {
+    let source = "a = 'x'; // note\n\n/** doc */\nb";
+    let tokens = tokenize_lossless(source, Language::JavaScript).unwrap();
+
+    // The original quote character is kept in the token text
+    assert_eq!(tokens[2].text, "'x'");
+    assert_eq!(tokens[2].kind, TokenKind::StringLiteral("x".to_string()));
+
+    // The line comment trails the semicolon on the same line
+    let semicolon = &tokens[3];
+    assert_eq!(semicolon.kind, TokenKind::Semicolon);
+    let trailing: Vec<_> = semicolon
+        .trailing_trivia
+        .iter()
+        .map(|t| t.text.as_str())
+        .collect();
+    assert_eq!(trailing, [" ", "// note"]);
+
+    // The blank line and the block comment lead the next statement
+    let b = &tokens[4];
+    assert_eq!(b.kind, TokenKind::Identifier("b".to_string()));
+    assert!(b.has_blank_line_before());
+    assert_eq!(b.newlines_before(), 3);
+    assert!(b
+        .leading_trivia
+        .iter()
+        .any(|t| t.kind == TokenKind::Comment(CommentKind::Block, "* doc ".to_string())));
+
+    let eof = &tokens[5];
+    assert_eq!(
+        (eof.span.start, eof.span.line, eof.span.column),
+        (source.len(), 4, 2)
+    );
+    assert!(!semicolon.has_blank_line_before());
+}
+
+#[test]
+fn test_template_literals_nest_substitutions() {
+    let source = "`a ${ {b: `c${d}`} } e`";
+    let tokens = tokenize(source, Language::JavaScript).unwrap();
+
+    let templates: Vec<_> = tokens
+        .iter()
+        .filter_map(|token| match &token.kind {
+            TokenKind::Template(kind, raw) => Some((*kind, raw.as_str(), token.text.as_str())),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(
+        templates,
+        [
+            (TemplateKind::Head, "a ", "`a ${"),
+            (TemplateKind::Head, "c", "`c${"),
+            (TemplateKind::Tail, "", "}`"),
+            (TemplateKind::Tail, " e", "} e`"),
+        ]
+    );
+
+    // The object literal's braces are ordinary tokens
+    let braces = tokens
+        .iter()
+        .filter(|token| matches!(token.kind, TokenKind::OpenBrace | TokenKind::CloseBrace))
+        .count();
+    assert_eq!(braces, 2);
+
+    let error = tokenize("`a ${b}", Language::JavaScript).unwrap_err();
+    assert!(error.to_string().contains("Unterminated template literal"));
+}
+
+#[test]
+fn test_hashbang_comment() {
+    let tokens = tokenize("\u{feff}#!/usr/bin/env node\nrun()", Language::JavaScript).unwrap();
+    let hashbang = tokens
+        .iter()
+        .find(|token| matches!(token.kind, TokenKind::Comment(..)))
+        .unwrap();
+    assert_eq!(
+        hashbang.kind,
+        TokenKind::Comment(CommentKind::Hashbang, "/usr/bin/env node".to_string())
+    );
+    assert_eq!(hashbang.text, "#!/usr/bin/env node");
+
+    // Only the first line of a file can be a hashbang
+    let tokens = tokenize("run()\n#!/usr/bin/env node", Language::JavaScript).unwrap();
+    assert!(!tokens
+        .iter()
+        .any(|token| matches!(token.kind, TokenKind::Comment(..))));
+}
+
+#[test]
+fn test_regex_literals_depend_on_context() {
+    let regexes = |source: &str| -> Vec<String> {
+        tokenize(source, Language::JavaScript)
+            .unwrap()
+            .into_iter()
+            .filter_map(|token| match token.kind {
+                TokenKind::RegexLiteral(raw) => Some(raw),
+                _ => None,
+            })
+            .collect()
+    };
+
+    assert_eq!(regexes("let r = /ab+c/gi;"), ["/ab+c/gi"]);
+    assert_eq!(regexes("f(/[/]\\//, /a\\/b/)"), ["/[/]\\//", "/a\\/b/"]);
+    assert_eq!(regexes("if (ok) return /x/.test(s)"), ["/x/"]);
+    assert!(regexes("let half = a / 2 / b;").is_empty());
+    assert!(regexes("x = (a) / b; y = c[0] / d; z = i++ / 2;").is_empty());
+
+    // A statement, and so a regex, follows control statement headers and blocks
+    assert_eq!(regexes("if (x) /re/.test(y)"), ["/re/"]);
+    assert_eq!(regexes("while (x) /a/g.exec(s)"), ["/a/g"]);
+    assert_eq!(regexes("for await (a of b) /c/.test(a)"), ["/c/"]);
+    assert_eq!(regexes("{}\n/d/.test(s)"), ["/d/"]);
+    assert_eq!(regexes("function f() {}\n/e/.test(s)"), ["/e/"]);
+    assert_eq!(regexes("label: {}\n/f/.test(s)"), ["/f/"]);
+    assert_eq!(regexes("for (const m of /a(b)/g.exec(s)) {}"), ["/a(b)/g"]);
+    assert_eq!(regexes("for (const [k] of /c/.exec(s)) {}"), ["/c/"]);
+
+    // An object literal, template, function or class expression is divided
+    assert!(regexes("a = {} / 2").is_empty());
+    assert!(regexes("a = { b: {} / 2 }").is_empty());
+    assert!(regexes("x = function () {} / 2").is_empty());
+    assert!(regexes("y = `${a}` / 2 / b").is_empty());
+    assert!(regexes("w = class {} / 2").is_empty());
+    assert!(regexes("if (a) f(x) / 2").is_empty());
+
+    // A keyword naming a property is an operand
+    assert!(regexes("ratio = sizes.new / sizes.default / 2;").is_empty());
+    assert!(regexes("x = a.return / 2; y = b?.this / c / d;").is_empty());
+    assert!(regexes("for (x = of / 2; of < 1; of++) {}").is_empty());
+
+    let error = tokenize("let r = /open\n", Language::JavaScript).unwrap_err();
+    assert!(error
+        .to_string()
+        .contains("Unterminated regular expression"));
+}
+
+#[test]
+fn test_numeric_literals() {
+    let source = "0xFF 0b1010 0O17 1_000_000 10n 0x1Fn 1.5e-3 .5 2E+10 1. a-1";
+    let numbers: Vec<_> = tokenize(source, Language::JavaScript)
+        .unwrap()
+        .into_iter()
+        .filter_map(|token| match token.kind {
+            TokenKind::NumberLiteral(number) => Some(number),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(
+        numbers,
+        [
+            "0xFF",
+            "0b1010",
+            "0O17",
+            "1_000_000",
+            "10n",
+            "0x1Fn",
+            "1.5e-3",
+            ".5",
+            "2E+10",
+            "1.",
+            "1"
+        ]
+    );
+
+    for source in ["0x", "1e+", "3in", "0b12", "1.5n"] {
+        let error = tokenize(source, Language::JavaScript).unwrap_err();
+        assert!(
+            error.to_string().contains("Invalid numeric literal"),
+            "{}",
+            source
+        );
+    }
+}
+
+#[test]
+fn test_unicode_identifiers_and_whitespace() {
+    let source = "café π \\u0061bc \\u{62}x_$\u{200d} 変数 \\u0069f if\u{a0}a\u{3000}b\u{2028}c";
+    let tokens = tokenize(source, Language::JavaScript).unwrap();
+
+    let significant: Vec<_> = tokens
+        .iter()
+        .filter(|token| !token.kind.is_trivia())
+        .map(|token| token.kind.clone())
+        .collect();
+    let identifier = |name: &str| TokenKind::Identifier(name.to_string());
+    assert_eq!(
+        significant,
+        [
+            identifier("café"),
+            identifier("π"),
+            identifier("\\u0061bc"),
+            identifier("\\u{62}x_$\u{200d}"),
+            identifier("変数"),
+            // An escaped keyword is not a keyword
+            identifier("\\u0069f"),
+            TokenKind::Keyword("if".to_string()),
+            identifier("a"),
+            identifier("b"),
+            identifier("c"),
+        ]
+    );
+
+    let separators = tokens
+        .iter()
+        .filter(|token| token.kind == TokenKind::Newline)
+        .count();
+    assert_eq!(separators, 1);
+
+    // A line comment ends at a line separator
+    let tokens = tokenize("// a\u{2029}b", Language::JavaScript).unwrap();
+    assert_eq!(tokens.last().unwrap().kind, identifier("b"));
+
+    for source in ["\\u00", "\\u{110000}", "\\u0031a", "a\\x", "\u{200c}a"] {
+        assert!(
+            tokenize(source, Language::JavaScript).is_err(),
+            "{}",
+            source
+        );
+    }
+}
+
+#[test]
+fn test_punctuators_use_longest_match() {
+    let source = "a=-1; f=>!x; b>>>=c; d**=e; g??=h; i?.j; k?.5:l; ...m; this.#n";
+    let kinds: Vec<_> = tokenize(source, Language::JavaScript)
+        .unwrap()
+        .into_iter()
+        .filter(|token| !token.kind.is_trivia())
+        .filter(|token| !matches!(token.kind, TokenKind::Identifier(_) | TokenKind::Semicolon))
+        .map(|token| token.kind)
+        .collect();
+    let operator = |op: &str| TokenKind::Operator(op.to_string());
+    assert_eq!(
+        kinds,
+        [
+            operator("="),
+            operator("-"),
+            TokenKind::NumberLiteral("1".to_string()),
+            operator("=>"),
+            operator("!"),
+            operator(">>>="),
+            operator("**="),
+            operator("??="),
+            TokenKind::QuestionDot,
+            operator("?"),
+            TokenKind::NumberLiteral(".5".to_string()),
+            TokenKind::Colon,
+            TokenKind::Ellipsis,
+            TokenKind::Keyword("this".to_string()),
+            TokenKind::Dot,
+            TokenKind::PrivateName("#n".to_string()),
+        ]
+    );
+}